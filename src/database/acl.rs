@@ -0,0 +1,106 @@
+use service_utils_rs::services::db::get_db;
+
+use crate::{
+    error::Result,
+    models::acl::{GraphGrant, GraphRole},
+};
+
+pub async fn create_graph_acl_table() -> Result<()> {
+    let query = r#"
+        DEFINE TABLE IF NOT EXISTS graph_acl SCHEMALESS PERMISSIONS FULL;
+
+        DEFINE FIELD IF NOT EXISTS graph_id ON TABLE graph_acl TYPE string;
+        DEFINE FIELD IF NOT EXISTS user_id ON TABLE graph_acl TYPE string;
+        DEFINE FIELD IF NOT EXISTS role ON TABLE graph_acl TYPE string ASSERT $value IN ["viewer", "editor", "runner"];
+        DEFINE FIELD IF NOT EXISTS granted_by ON TABLE graph_acl TYPE string;
+        DEFINE FIELD IF NOT EXISTS created_at ON TABLE graph_acl TYPE datetime DEFAULT time::now() READONLY;
+
+        DEFINE INDEX IF NOT EXISTS unique_graph_acl ON TABLE graph_acl FIELDS graph_id, user_id UNIQUE;
+        DEFINE INDEX IF NOT EXISTS idx_graph_acl_user ON TABLE graph_acl FIELDS user_id;
+    "#;
+
+    let db = get_db();
+    db.query(query).await?;
+
+    Ok(())
+}
+
+/// 同一用户在同一图表上只有一条授权记录，重复授权会覆盖角色
+pub async fn upsert_grant(
+    graph_id: &str,
+    user_id: &str,
+    role: GraphRole,
+    granted_by: &str,
+) -> Result<()> {
+    let query = r#"
+        UPSERT type::thing("graph_acl", [$graph_id, $user_id])
+        SET graph_id = $graph_id, user_id = $user_id, role = $role, granted_by = $granted_by;
+    "#;
+
+    let db = get_db();
+    db.query(query)
+        .bind(("graph_id", graph_id.to_owned()))
+        .bind(("user_id", user_id.to_owned()))
+        .bind(("role", role))
+        .bind(("granted_by", granted_by.to_owned()))
+        .await?;
+
+    Ok(())
+}
+
+pub async fn delete_grant(graph_id: &str, user_id: &str) -> Result<()> {
+    let query = r#"
+        DELETE graph_acl WHERE graph_id = $graph_id AND user_id = $user_id;
+    "#;
+
+    let db = get_db();
+    db.query(query)
+        .bind(("graph_id", graph_id.to_owned()))
+        .bind(("user_id", user_id.to_owned()))
+        .await?;
+
+    Ok(())
+}
+
+pub async fn delete_grants_by_graph(graph_id: &str) -> Result<()> {
+    let query = r#"
+        DELETE graph_acl WHERE graph_id = $graph_id;
+    "#;
+
+    let db = get_db();
+    db.query(query)
+        .bind(("graph_id", graph_id.to_owned()))
+        .await?;
+
+    Ok(())
+}
+
+pub async fn get_grants_by_graph(graph_id: &str) -> Result<Vec<GraphGrant>> {
+    let query = r#"
+        SELECT graph_id, user_id, role, granted_by, created_at FROM graph_acl
+        WHERE graph_id = $graph_id ORDER BY created_at ASC;
+    "#;
+
+    let db = get_db();
+    let mut response = db
+        .query(query)
+        .bind(("graph_id", graph_id.to_owned()))
+        .await?;
+    let r = response.take(0)?;
+    Ok(r)
+}
+
+pub async fn get_granted_role(graph_id: &str, user_id: &str) -> Result<Option<GraphRole>> {
+    let query = r#"
+        SELECT VALUE role FROM graph_acl WHERE graph_id = $graph_id AND user_id = $user_id;
+    "#;
+
+    let db = get_db();
+    let mut response = db
+        .query(query)
+        .bind(("graph_id", graph_id.to_owned()))
+        .bind(("user_id", user_id.to_owned()))
+        .await?;
+    let r = response.take(0)?;
+    Ok(r)
+}
@@ -1,11 +1,16 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use service_utils_rs::services::db::get_db;
 use surrealdb::Uuid;
 
 use crate::{
     error::{Error, Result},
-    models::{Edge, Graph, Node, graph::GraphSummary},
+    models::{
+        Edge, Graph, Node,
+        bundle::BundleGraph,
+        graph::{GraphSortField, GraphSummary, ListGraphsQuery, PatchGraphRequest, SortOrder},
+    },
+    utils::{graph::regenerate_node_ids, template::GraphTemplate},
 };
 
 pub async fn create_graph_table() -> Result<()> {
@@ -22,6 +27,8 @@ pub async fn create_graph_table() -> Result<()> {
         DEFINE FIELD IF NOT EXISTS end_node ON TABLE graph TYPE string;
         DEFINE FIELD IF NOT EXISTS created_at ON TABLE graph TYPE datetime DEFAULT time::now() READONLY;
         DEFINE FIELD IF NOT EXISTS updated_at ON TABLE graph TYPE datetime VALUE time::now();
+        DEFINE FIELD IF NOT EXISTS deleted_at ON TABLE graph TYPE option<datetime>;
+        DEFINE FIELD IF NOT EXISTS limits ON TABLE graph FLEXIBLE TYPE option<object>;
 
         DEFINE INDEX IF NOT EXISTS unique_graph_uuid ON TABLE graph FIELDS uuid UNIQUE;
         DEFINE INDEX IF NOT EXISTS idx_graph_owner ON TABLE graph FIELDS owner;
@@ -63,6 +70,59 @@ impl GraphInput {
         }
     }
 
+    pub fn from_template(
+        owner: &str,
+        name: &str,
+        description: &str,
+        template: &GraphTemplate,
+    ) -> Self {
+        let (nodes, edges) = template.build();
+        GraphInput {
+            uuid: Uuid::new_v4().to_string(),
+            owner: owner.to_string(),
+            name: name.to_string(),
+            description: description.to_string(),
+            nodes,
+            edges,
+            start_node: "start".to_string(),
+            end_node: "end".to_string(),
+        }
+    }
+
+    pub fn from_bundle(owner: &str, graph: BundleGraph) -> Self {
+        GraphInput {
+            uuid: Uuid::new_v4().to_string(),
+            owner: owner.to_string(),
+            name: graph.name,
+            description: graph.description,
+            nodes: graph.nodes,
+            edges: graph.edges,
+            start_node: graph.start_node,
+            end_node: graph.end_node,
+        }
+    }
+
+    pub fn clone_from(owner: &str, graph: Graph, name: String, regenerate_ids: bool) -> Self {
+        let mut nodes = graph.nodes;
+        let mut edges = graph.edges;
+        let mut start_node = graph.start_node;
+        let mut end_node = graph.end_node;
+        if regenerate_ids {
+            regenerate_node_ids(&mut nodes, &mut edges, &mut start_node, &mut end_node);
+        }
+
+        GraphInput {
+            uuid: Uuid::new_v4().to_string(),
+            owner: owner.to_string(),
+            name,
+            description: graph.description,
+            nodes,
+            edges,
+            start_node,
+            end_node,
+        }
+    }
+
     // pub fn demo(owner: &str, name: &str, description: &str) -> Self {
     //     let start_node = Node::new_start_node();
     //     let end_node = Node::new_end_node();
@@ -82,50 +142,201 @@ impl GraphInput {
     // }
 }
 
-// pub async fn create_graph(input: GraphInput) -> Result<()> {
-//     let db = get_db();
-//     let _r: Option<Graph> = db.create(("graph", &input.uuid)).content(input).await?;
-//     Ok(())
-// }
-
-pub async fn default_with_owner(owner: &str, name: &str, description: &str) -> Result<String> {
+pub async fn insert_graph(input: GraphInput) -> Result<Graph> {
     let db = get_db();
-    let input = GraphInput::default_with_owner(owner, name, description);
     let r: Option<Graph> = db.create(("graph", &input.uuid)).content(input).await?;
-    if let Some(graph) = r {
-        Ok(graph.uuid)
-    } else {
-        Err(Error::GraphCreationError)
+    r.ok_or(Error::GraphCreationError)
+}
+
+/// 带版本号时只有 `updated_at` 与之相同才会更新，用于乐观并发控制
+fn version_condition(version: Option<&str>) -> &'static str {
+    match version {
+        Some(_) => "AND updated_at = type::datetime($version)",
+        None => "",
     }
 }
 
-pub async fn update_graph_by_id(id: &str, nodes: Vec<Node>, edges: Vec<Edge>) -> Result<()> {
-    let query = r#"
-        UPDATE graph SET nodes = $nodes, edges = $edges WHERE uuid = $id;
-    "#;
+pub async fn update_graph_by_id(
+    id: &str,
+    nodes: &[Node],
+    edges: &[Edge],
+    version: Option<&str>,
+) -> Result<Option<Graph>> {
+    let query = format!(
+        "UPDATE graph SET nodes = $nodes, edges = $edges WHERE uuid = $id {} RETURN AFTER;",
+        version_condition(version)
+    );
 
     let nodes_value: Value = serde_json::to_value(nodes)?.into();
     let edges_value: Value = serde_json::to_value(edges)?.into();
 
     let db = get_db();
-    db.query(query)
+    let mut response = db
+        .query(query)
         .bind(("id", id.to_owned()))
         .bind(("nodes", nodes_value))
         .bind(("edges", edges_value))
+        .bind(("version", version.map(str::to_owned)))
         .await?;
+    let r = response.take(0)?;
+    Ok(r)
+}
 
-    Ok(())
+pub async fn replace_graph_content(
+    id: &str,
+    nodes: &[Node],
+    edges: &[Edge],
+    start_node: &str,
+    end_node: &str,
+    version: Option<&str>,
+) -> Result<Option<Graph>> {
+    let query = format!(
+        "UPDATE graph SET nodes = $nodes, edges = $edges, start_node = $start_node, end_node = \
+         $end_node WHERE uuid = $id {} RETURN AFTER;",
+        version_condition(version)
+    );
+
+    let nodes_value: Value = serde_json::to_value(nodes)?;
+    let edges_value: Value = serde_json::to_value(edges)?;
+
+    let db = get_db();
+    let mut response = db
+        .query(query)
+        .bind(("id", id.to_owned()))
+        .bind(("nodes", nodes_value))
+        .bind(("edges", edges_value))
+        .bind(("start_node", start_node.to_owned()))
+        .bind(("end_node", end_node.to_owned()))
+        .bind(("version", version.map(str::to_owned)))
+        .await?;
+    let r = response.take(0)?;
+    Ok(r)
 }
 
+pub async fn patch_graph_by_id(
+    id: &str,
+    patch: &PatchGraphRequest,
+    version: Option<&str>,
+) -> Result<Option<Graph>> {
+    let query = format!(
+        "UPDATE graph MERGE $patch WHERE uuid = $id {} RETURN AFTER;",
+        version_condition(version)
+    );
+
+    let patch_value: Value = serde_json::to_value(patch)?;
+
+    let db = get_db();
+    let mut response = db
+        .query(query)
+        .bind(("id", id.to_owned()))
+        .bind(("patch", patch_value))
+        .bind(("version", version.map(str::to_owned)))
+        .await?;
+    let r = response.take(0)?;
+    Ok(r)
+}
+
+/// 只返回未删除的图表，回收站中的图表视为不存在
 pub async fn get_graph_by_id(id: &str) -> Result<Option<Graph>> {
     let db = get_db();
     let r: Option<Graph> = db.select(("graph", id.to_owned())).await?;
-    Ok(r)
+    Ok(r.filter(|g| g.deleted_at.is_none()))
+}
+
+pub async fn get_trashed_graph_by_id(id: &str) -> Result<Option<Graph>> {
+    let db = get_db();
+    let r: Option<Graph> = db.select(("graph", id.to_owned())).await?;
+    Ok(r.filter(|g| g.deleted_at.is_some()))
+}
+
+pub const MAX_PAGE_LIMIT: u64 = 100;
+
+#[derive(Deserialize, Debug)]
+struct CountRow {
+    count: u64,
+}
+
+pub async fn get_graphs_by_owner(
+    owner: &str,
+    query: &ListGraphsQuery,
+) -> Result<(Vec<GraphSummary>, u64)> {
+    // ORDER BY 的字段和方向不能通过参数绑定，只能从固定的枚举值拼接
+    let sort_field = match query.sort {
+        GraphSortField::Name => "name",
+        GraphSortField::CreatedAt => "created_at",
+        GraphSortField::UpdatedAt => "updated_at",
+    };
+    let order = match query.order {
+        SortOrder::Asc => "ASC",
+        SortOrder::Desc => "DESC",
+    };
+    let name_filter = match query.name {
+        Some(_) => "AND string::contains(string::lowercase(name), string::lowercase($name))",
+        None => "",
+    };
+
+    // 自己拥有的图表加上通过 graph_acl 共享给自己的图表，并带上当前用户的有效角色
+    let sql = format!(
+        r#"
+        LET $shared = (SELECT VALUE graph_id FROM graph_acl WHERE user_id = $owner);
+        SELECT uuid, name, description, array::len(nodes) AS node_count, created_at, updated_at,
+            IF owner = $owner THEN "owner" ELSE (
+                SELECT VALUE role FROM graph_acl WHERE graph_id = $parent.uuid AND user_id = $owner
+            )[0] END AS role
+        FROM graph WHERE (owner = $owner OR uuid IN $shared) AND deleted_at IS NONE {name_filter}
+        ORDER BY {sort_field} {order} LIMIT $limit START $offset;
+        SELECT count() FROM graph
+        WHERE (owner = $owner OR uuid IN $shared) AND deleted_at IS NONE {name_filter}
+        GROUP ALL;
+        "#
+    );
+
+    let db = get_db();
+    let mut response = db
+        .query(sql)
+        .bind(("owner", owner.to_owned()))
+        .bind(("name", query.name.clone()))
+        .bind(("limit", query.limit.clamp(1, MAX_PAGE_LIMIT)))
+        .bind(("offset", query.offset))
+        .await?;
+    let items = response.take(1)?;
+    let count: Option<CountRow> = response.take(2)?;
+    Ok((items, count.map_or(0, |c| c.count)))
+}
+
+pub async fn delete_graph_by_id(id: &str) -> Result<()> {
+    let db = get_db();
+    let _: Option<Graph> = db.delete(("graph", id.to_owned())).await?;
+    Ok(())
 }
 
-pub async fn get_graphs_by_owner(owner: &str) -> Result<Vec<GraphSummary>> {
+pub async fn trash_graph_by_id(id: &str) -> Result<()> {
     let query = r#"
-        SELECT uuid, name, description FROM graph WHERE owner = $owner;
+        UPDATE graph SET deleted_at = time::now() WHERE uuid = $id;
+    "#;
+
+    let db = get_db();
+    db.query(query).bind(("id", id.to_owned())).await?;
+
+    Ok(())
+}
+
+pub async fn restore_graph_by_id(id: &str) -> Result<()> {
+    let query = r#"
+        UPDATE graph SET deleted_at = NONE WHERE uuid = $id;
+    "#;
+
+    let db = get_db();
+    db.query(query).bind(("id", id.to_owned())).await?;
+
+    Ok(())
+}
+
+pub async fn get_trashed_graphs_by_owner(owner: &str) -> Result<Vec<GraphSummary>> {
+    let query = r#"
+        SELECT uuid, name, description, array::len(nodes) AS node_count, created_at, updated_at,
+            deleted_at, "owner" AS role
+        FROM graph WHERE owner = $owner AND deleted_at IS NOT NONE ORDER BY deleted_at DESC;
     "#;
 
     let db = get_db();
@@ -134,23 +345,25 @@ pub async fn get_graphs_by_owner(owner: &str) -> Result<Vec<GraphSummary>> {
     Ok(r)
 }
 
-pub async fn get_owner_graph_by_id(id: &str, owner: &str) -> Result<Option<Graph>> {
+/// 永久删除在回收站中超过保留天数的图表及其历史版本，返回删除的数量
+pub async fn purge_expired_graphs(retention_days: u64) -> Result<usize> {
     let query = r#"
-        SELECT * FROM graph WHERE uuid = $id AND owner = $owner;
+        LET $purged = (
+            DELETE graph WHERE deleted_at IS NOT NONE
+                AND deleted_at < time::now() - type::duration($retention)
+            RETURN BEFORE
+        );
+        DELETE graph_revision WHERE graph_id IN $purged.uuid;
+        DELETE graph_acl WHERE graph_id IN $purged.uuid;
+        DELETE workflow_run WHERE graph_id IN $purged.uuid;
+        RETURN $purged.uuid;
     "#;
 
     let db = get_db();
     let mut response = db
         .query(query)
-        .bind(("id", id.to_owned()))
-        .bind(("owner", owner.to_owned()))
+        .bind(("retention", format!("{}d", retention_days)))
         .await?;
-    let r = response.take(0)?;
-    Ok(r)
-}
-
-pub async fn delete_graph_by_id(id: &str) -> Result<()> {
-    let db = get_db();
-    let _: Option<Graph> = db.delete(("graph", id.to_owned())).await?;
-    Ok(())
+    let purged: Vec<String> = response.take(4)?;
+    Ok(purged.len())
 }
@@ -1,8 +1,20 @@
+pub mod acl;
 pub mod graph;
+pub mod revision;
+pub mod run;
 
-use crate::{database::graph::create_graph_table, error::Result};
+use crate::{
+    database::{
+        acl::create_graph_acl_table, graph::create_graph_table,
+        revision::create_graph_revision_table, run::create_workflow_run_table,
+    },
+    error::Result,
+};
 
 pub async fn create_tables() -> Result<()> {
     create_graph_table().await?;
+    create_graph_acl_table().await?;
+    create_graph_revision_table().await?;
+    create_workflow_run_table().await?;
     Ok(())
 }
@@ -0,0 +1,162 @@
+use serde::Serialize;
+use service_utils_rs::services::db::get_db;
+use surrealdb::Uuid;
+
+use crate::{
+    error::{Error, Result},
+    models::{
+        Edge, Graph, Node,
+        revision::{GraphRevision, GraphRevisionSummary},
+    },
+};
+
+pub async fn create_graph_revision_table() -> Result<()> {
+    let query = r#"
+        DEFINE TABLE IF NOT EXISTS graph_revision SCHEMALESS PERMISSIONS FULL;
+
+        DEFINE FIELD IF NOT EXISTS uuid ON TABLE graph_revision TYPE string READONLY;
+        DEFINE FIELD IF NOT EXISTS graph_id ON TABLE graph_revision TYPE string READONLY;
+        DEFINE FIELD IF NOT EXISTS nodes ON TABLE graph_revision TYPE array<object> READONLY;
+        DEFINE FIELD IF NOT EXISTS edges ON TABLE graph_revision TYPE array<object> READONLY;
+        DEFINE FIELD IF NOT EXISTS start_node ON TABLE graph_revision TYPE string READONLY;
+        DEFINE FIELD IF NOT EXISTS end_node ON TABLE graph_revision TYPE string READONLY;
+        DEFINE FIELD IF NOT EXISTS author ON TABLE graph_revision TYPE string READONLY;
+        DEFINE FIELD IF NOT EXISTS message ON TABLE graph_revision TYPE option<string> READONLY;
+        DEFINE FIELD IF NOT EXISTS created_at ON TABLE graph_revision TYPE datetime DEFAULT time::now() READONLY;
+
+        DEFINE INDEX IF NOT EXISTS unique_graph_revision_uuid ON TABLE graph_revision FIELDS uuid UNIQUE;
+        DEFINE INDEX IF NOT EXISTS idx_graph_revision_graph ON TABLE graph_revision FIELDS graph_id;
+    "#;
+
+    let db = get_db();
+    db.query(query).await?;
+
+    Ok(())
+}
+
+#[derive(Serialize, Debug)]
+pub(crate) struct GraphRevisionInput<'a> {
+    pub uuid: String,
+    pub graph_id: &'a str,
+    pub nodes: &'a [Node],
+    pub edges: &'a [Edge],
+    pub start_node: &'a str,
+    pub end_node: &'a str,
+    pub author: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<&'a str>,
+}
+
+impl<'a> GraphRevisionInput<'a> {
+    pub fn new(
+        graph_id: &'a str,
+        nodes: &'a [Node],
+        edges: &'a [Edge],
+        start_node: &'a str,
+        end_node: &'a str,
+        author: &'a str,
+        message: Option<&'a str>,
+    ) -> Self {
+        GraphRevisionInput {
+            uuid: Uuid::new_v4().to_string(),
+            graph_id,
+            nodes,
+            edges,
+            start_node,
+            end_node,
+            author,
+            message,
+        }
+    }
+}
+
+pub async fn create_graph_revision(input: GraphRevisionInput<'_>) -> Result<String> {
+    let db = get_db();
+    let content = serde_json::to_value(&input)?;
+    let r: Option<GraphRevision> = db
+        .create(("graph_revision", &input.uuid))
+        .content(content)
+        .await?;
+    if let Some(revision) = r {
+        Ok(revision.uuid)
+    } else {
+        Err(Error::RevisionCreationError)
+    }
+}
+
+/// 在覆盖图表内容之前调用：图表还没有任何历史版本时（如早于版本功能创建的图表），
+/// 先把当前内容记为一个版本，保证覆盖前的状态可以恢复
+pub async fn ensure_head_revision(graph: &Graph) -> Result<()> {
+    let query = r#"
+        SELECT uuid, graph_id, author, message, created_at FROM graph_revision
+        WHERE graph_id = $graph_id LIMIT 1;
+    "#;
+
+    let db = get_db();
+    let mut response = db
+        .query(query)
+        .bind(("graph_id", graph.uuid.clone()))
+        .await?;
+    let existing: Option<GraphRevisionSummary> = response.take(0)?;
+    if existing.is_some() {
+        return Ok(());
+    }
+
+    let input = GraphRevisionInput::new(
+        &graph.uuid,
+        &graph.nodes,
+        &graph.edges,
+        &graph.start_node,
+        &graph.end_node,
+        &graph.owner,
+        Some("state before first recorded change"),
+    );
+    create_graph_revision(input).await?;
+    Ok(())
+}
+
+pub async fn get_revisions_by_graph(graph_id: &str) -> Result<Vec<GraphRevisionSummary>> {
+    let query = r#"
+        SELECT uuid, graph_id, author, message, created_at FROM graph_revision
+        WHERE graph_id = $graph_id ORDER BY created_at DESC;
+    "#;
+
+    let db = get_db();
+    let mut response = db
+        .query(query)
+        .bind(("graph_id", graph_id.to_owned()))
+        .await?;
+    let r = response.take(0)?;
+    Ok(r)
+}
+
+pub async fn get_graph_revision_by_id(
+    graph_id: &str,
+    revision_id: &str,
+) -> Result<Option<GraphRevision>> {
+    let query = r#"
+        SELECT * FROM graph_revision WHERE uuid = $id AND graph_id = $graph_id;
+    "#;
+
+    let db = get_db();
+    let mut response = db
+        .query(query)
+        .bind(("id", revision_id.to_owned()))
+        .bind(("graph_id", graph_id.to_owned()))
+        .await?;
+    let r = response.take(0)?;
+    Ok(r)
+}
+
+pub async fn delete_revisions_by_graph(graph_id: &str) -> Result<()> {
+    let query = r#"
+        DELETE graph_revision WHERE graph_id = $graph_id;
+    "#;
+
+    let db = get_db();
+    db.query(query)
+        .bind(("graph_id", graph_id.to_owned()))
+        .await?;
+
+    Ok(())
+}
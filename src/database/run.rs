@@ -0,0 +1,158 @@
+use serde::Serialize;
+use serde_json::Value;
+use service_utils_rs::services::db::get_db;
+
+use crate::{
+    error::Result,
+    models::{
+        run::{NodeTrace, RunRecord, RunRecordSummary},
+        workflow::{RunStatus, WorkflowOutput},
+    },
+};
+
+pub async fn create_workflow_run_table() -> Result<()> {
+    let query = r#"
+        DEFINE TABLE IF NOT EXISTS workflow_run SCHEMALESS PERMISSIONS FULL;
+
+        DEFINE FIELD IF NOT EXISTS uuid ON TABLE workflow_run TYPE string READONLY;
+        DEFINE FIELD IF NOT EXISTS graph_id ON TABLE workflow_run TYPE string READONLY;
+        DEFINE FIELD IF NOT EXISTS user_id ON TABLE workflow_run TYPE string READONLY;
+        DEFINE FIELD IF NOT EXISTS status ON TABLE workflow_run TYPE string;
+        DEFINE FIELD IF NOT EXISTS graph ON TABLE workflow_run TYPE object READONLY;
+        DEFINE FIELD IF NOT EXISTS input ON TABLE workflow_run TYPE option<object> READONLY;
+        DEFINE FIELD IF NOT EXISTS output ON TABLE workflow_run TYPE option<object>;
+        DEFINE FIELD IF NOT EXISTS error ON TABLE workflow_run TYPE option<string>;
+        DEFINE FIELD IF NOT EXISTS trace ON TABLE workflow_run TYPE array<object> DEFAULT [];
+        DEFINE FIELD IF NOT EXISTS started_at ON TABLE workflow_run TYPE datetime DEFAULT time::now() READONLY;
+        DEFINE FIELD IF NOT EXISTS finished_at ON TABLE workflow_run TYPE option<datetime>;
+
+        DEFINE INDEX IF NOT EXISTS unique_workflow_run_uuid ON TABLE workflow_run FIELDS uuid UNIQUE;
+        DEFINE INDEX IF NOT EXISTS idx_workflow_run_graph ON TABLE workflow_run FIELDS graph_id;
+    "#;
+
+    let db = get_db();
+    db.query(query).await?;
+
+    Ok(())
+}
+
+#[derive(Serialize, Debug)]
+pub(crate) struct RunRecordInput {
+    pub uuid: String,
+    pub graph_id: String,
+    pub user_id: String,
+    pub status: RunStatus,
+    pub graph: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input: Option<Value>,
+}
+
+pub async fn create_run_record(input: RunRecordInput) -> Result<()> {
+    let db = get_db();
+    let _: Option<RunRecord> = db
+        .create(("workflow_run", &input.uuid))
+        .content(input)
+        .await?;
+    Ok(())
+}
+
+pub async fn finish_run_record(
+    id: &str,
+    status: RunStatus,
+    output: Option<WorkflowOutput>,
+    error: Option<String>,
+    trace: Vec<NodeTrace>,
+) -> Result<()> {
+    let query = r#"
+        UPDATE workflow_run SET status = $status, output = $output, error = $error,
+            trace = $trace, finished_at = time::now()
+        WHERE uuid = $id;
+    "#;
+
+    let db = get_db();
+    db.query(query)
+        .bind(("id", id.to_owned()))
+        .bind(("status", status))
+        .bind(("output", output))
+        .bind(("error", error))
+        .bind(("trace", trace))
+        .await?;
+
+    Ok(())
+}
+
+/// 进程重启后，上次仍在运行的记录不会再有结果，统一标记为失败
+pub async fn fail_orphaned_runs() -> Result<()> {
+    let query = r#"
+        UPDATE workflow_run SET status = $failed, error = $error, finished_at = time::now()
+        WHERE status = $running;
+    "#;
+
+    let db = get_db();
+    db.query(query)
+        .bind(("failed", RunStatus::Failed))
+        .bind(("running", RunStatus::Running))
+        .bind(("error", "run was interrupted by a server restart"))
+        .await?;
+
+    Ok(())
+}
+
+pub async fn get_runs_by_graph(graph_id: &str) -> Result<Vec<RunRecordSummary>> {
+    let query = r#"
+        SELECT uuid, graph_id, user_id, status, error, started_at, finished_at FROM workflow_run
+        WHERE graph_id = $graph_id ORDER BY started_at DESC;
+    "#;
+
+    let db = get_db();
+    let mut response = db
+        .query(query)
+        .bind(("graph_id", graph_id.to_owned()))
+        .await?;
+    let r = response.take(0)?;
+    Ok(r)
+}
+
+pub async fn get_run_record(graph_id: &str, run_id: &str) -> Result<Option<RunRecord>> {
+    let query = r#"
+        SELECT * FROM workflow_run WHERE uuid = $id AND graph_id = $graph_id;
+    "#;
+
+    let db = get_db();
+    let mut response = db
+        .query(query)
+        .bind(("id", run_id.to_owned()))
+        .bind(("graph_id", graph_id.to_owned()))
+        .await?;
+    let r = response.take(0)?;
+    Ok(r)
+}
+
+/// 按运行 ID 读取当前用户的运行记录，内存中已没有该运行时使用
+pub async fn get_user_run_record(run_id: &str, user_id: &str) -> Result<Option<RunRecord>> {
+    let query = r#"
+        SELECT * FROM workflow_run WHERE uuid = $id AND user_id = $user_id;
+    "#;
+
+    let db = get_db();
+    let mut response = db
+        .query(query)
+        .bind(("id", run_id.to_owned()))
+        .bind(("user_id", user_id.to_owned()))
+        .await?;
+    let r = response.take(0)?;
+    Ok(r)
+}
+
+pub async fn delete_runs_by_graph(graph_id: &str) -> Result<()> {
+    let query = r#"
+        DELETE workflow_run WHERE graph_id = $graph_id;
+    "#;
+
+    let db = get_db();
+    db.query(query)
+        .bind(("graph_id", graph_id.to_owned()))
+        .await?;
+
+    Ok(())
+}
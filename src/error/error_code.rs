@@ -17,4 +17,24 @@ status_error_codes! {
     (-2, INVALID_PARAMS, "invalid params");
     (-101, GRAPH_NOT_FOUND, "graph not found");
     (-102, GRAPH_NOT_BELONG_TO_USER, "graph does not belong to user");
+    (-103, GRAPH_REVISION_NOT_FOUND, "graph revision not found");
+    (-104, GRAPH_VALIDATION_FAILED, "graph validation failed");
+    (-105, GRAPH_NODE_NOT_FOUND, "node not found in graph");
+    (-106, GRAPH_VERSION_CONFLICT, "graph version conflict");
+    (-107, GRAPH_BUNDLE_VERSION_UNSUPPORTED, "graph bundle version is not supported");
+    (-108, INVALID_GRAPH_BUNDLE, "invalid graph bundle");
+    (-109, GRAPH_PERMISSION_DENIED, "insufficient permission on graph");
+    (-110, GRAPH_TEMPLATE_NOT_FOUND, "graph template not found");
+    (-111, RUN_NOT_FOUND, "run not found");
+    (-112, RUN_ALREADY_FINISHED, "run already finished");
+    (-113, RUN_RECORD_NOT_FOUND, "run record not found");
+    (-114, OUTPUT_FORMAT_MISMATCH, "workflow output cannot be converted to requested format");
+    (-115, RUN_TIMEOUT, "run exceeded the timeout limit");
+    (-116, RUN_NODE_LIMIT_EXCEEDED, "run exceeded the node execution limit");
+    (-117, RUN_OUTPUT_TOO_LARGE, "run output exceeded the size limit");
+    (-118, RUN_QUEUE_FULL, "run queue is full");
+    (-119, UNKNOWN_NODE_KIND, "unknown node type");
+    (-120, HTTP_NODE_NOT_RUNNABLE, "http node cannot be run");
+    (-121, HTTP_NODE_HOST_NOT_ALLOWED, "http node host is not allowed");
+    (-122, HTTP_NODE_REQUEST_FAILED, "http node request failed");
 }
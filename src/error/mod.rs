@@ -18,6 +18,18 @@ pub enum Error {
 
     #[error("failed to create graph")]
     GraphCreationError,
+
+    #[error("failed to create graph revision")]
+    RevisionCreationError,
+
+    #[error("unsupported bundle format version: {0}")]
+    UnsupportedBundleVersion(u64),
+
+    #[error("invalid bundle format version: {0}")]
+    InvalidBundleVersion(String),
+
+    #[error("invalid config: {0}")]
+    InvalidConfig(&'static str),
 }
 
 pub type Result<T, E = Error> = core::result::Result<T, E>;
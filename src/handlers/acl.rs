@@ -0,0 +1,114 @@
+use axum::{Extension, Json, extract::Path, http::StatusCode};
+use service_utils_rs::services::http::{
+    CommonError, CommonResponse, IntoCommonResponse,
+    middleware::auth_mw::UserId,
+    response::{CommonOk, Empty, ResponseResult},
+};
+use validator::Validate;
+
+use crate::{
+    database::acl::{delete_grant, get_grants_by_graph, upsert_grant},
+    error::error_code,
+    handlers::get_authorized_graph,
+    models::acl::{GrantGraphRequest, GraphGrant, GraphPermission, GraphRole},
+};
+
+#[utoipa::path(
+    get,
+    path = "/share/{id}",
+    responses(
+        (status = 200, description = "Succeed", body = CommonResponse<Vec<GraphGrant>>),
+        (status = 403, description = "No access to graph", body = CommonError),
+        (status = 404, description = "Graph not found", body = CommonError),
+        (status = 500, description = "Error", body = CommonError)
+    ),
+    description = "获取图表的共享列表",
+    tag = "Graph",
+    security(("Bearer" = [])),
+)]
+pub async fn list_grants(
+    Extension(UserId(user_id)): Extension<UserId>,
+    Path(id): Path<String>,
+) -> ResponseResult<Vec<GraphGrant>> {
+    get_authorized_graph(&id, &user_id, GraphPermission::View).await?;
+
+    let grants = get_grants_by_graph(&id).await.map_err(|_e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(error_code::SERVER_ERROR.into()),
+        )
+    })?;
+    Ok(grants.into_common_response().to_json())
+}
+
+#[utoipa::path(
+    post,
+    path = "/share/{id}",
+    request_body = GrantGraphRequest,
+    responses(
+        (status = 200, description = "Succeed", body = CommonResponse<Empty>),
+        (status = 400, description = "Invalid params", body = CommonError),
+        (status = 403, description = "Only the owner can share the graph", body = CommonError),
+        (status = 404, description = "Graph not found", body = CommonError),
+        (status = 500, description = "Error", body = CommonError)
+    ),
+    description = "将图表共享给其他用户，重复授权会覆盖原有角色",
+    tag = "Graph",
+    security(("Bearer" = [])),
+)]
+pub async fn grant_graph(
+    Extension(UserId(user_id)): Extension<UserId>,
+    Path(id): Path<String>,
+    Json(payload): Json<GrantGraphRequest>,
+) -> ResponseResult<Empty> {
+    let (graph, _) = get_authorized_graph(&id, &user_id, GraphPermission::Manage).await?;
+
+    // owner 角色不能授予，也不能给所有者自己授权
+    if payload.validate().is_err()
+        || payload.role == GraphRole::Owner
+        || payload.user_id == graph.owner
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(error_code::INVALID_PARAMS.into()),
+        ));
+    }
+
+    upsert_grant(&id, &payload.user_id, payload.role, &user_id)
+        .await
+        .map_err(|_e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(error_code::SERVER_ERROR.into()),
+            )
+        })?;
+    Ok(CommonOk::default().to_json())
+}
+
+#[utoipa::path(
+    delete,
+    path = "/share/{id}/{user_id}",
+    responses(
+        (status = 200, description = "Succeed", body = CommonResponse<Empty>),
+        (status = 403, description = "Only the owner can revoke access", body = CommonError),
+        (status = 404, description = "Graph not found", body = CommonError),
+        (status = 500, description = "Error", body = CommonError)
+    ),
+    description = "撤销用户对图表的共享权限",
+    tag = "Graph",
+    security(("Bearer" = [])),
+)]
+pub async fn revoke_graph(
+    Extension(UserId(user_id)): Extension<UserId>,
+    Path((id, grantee)): Path<(String, String)>,
+) -> ResponseResult<Empty> {
+    get_authorized_graph(&id, &user_id, GraphPermission::Manage).await?;
+
+    delete_grant(&id, &grantee).await.map_err(|_e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(error_code::SERVER_ERROR.into()),
+        )
+    })?;
+    Ok(CommonOk::default().to_json())
+}
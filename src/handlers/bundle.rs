@@ -0,0 +1,112 @@
+use axum::{Extension, Json, extract::Path, http::StatusCode};
+use serde_json::Value;
+use service_utils_rs::services::http::{
+    CommonError, CommonResponse, IntoCommonResponse, middleware::auth_mw::UserId,
+    response::ResponseResult,
+};
+
+use crate::{
+    database::{
+        graph::{GraphInput, insert_graph},
+        revision::{GraphRevisionInput, create_graph_revision},
+    },
+    error::{Error, error_code},
+    handlers::{ensure_known_node_kinds, ensure_valid_nodes, get_authorized_graph},
+    models::{acl::GraphPermission, bundle::GraphBundle, graph::CreateGraphResponse},
+};
+
+#[utoipa::path(
+    get,
+    path = "/export/{id}",
+    responses(
+        (status = 200, description = "Succeed", body = CommonResponse<GraphBundle>),
+        (status = 404, description = "Graph not found", body = CommonError),
+        (status = 500, description = "Error", body = CommonError)
+    ),
+    description = "导出图表为可移植的数据包",
+    tag = "Graph",
+    security(("Bearer" = [])),
+)]
+pub async fn export_graph(
+    Extension(UserId(user_id)): Extension<UserId>,
+    Path(id): Path<String>,
+) -> ResponseResult<GraphBundle> {
+    let (graph, _) = get_authorized_graph(&id, &user_id, GraphPermission::View).await?;
+    let bundle: GraphBundle = graph.into();
+    Ok(bundle.into_common_response().to_json())
+}
+
+#[utoipa::path(
+    post,
+    path = "/import",
+    request_body = GraphBundle,
+    responses(
+        (status = 200, description = "Succeed", body = CommonResponse<CreateGraphResponse>),
+        (status = 400, description = "Invalid or unsupported bundle", body = CommonError),
+        (status = 500, description = "Error", body = CommonError)
+    ),
+    description = "从数据包导入图表，生成新的 ID 并归属当前用户",
+    tag = "Graph",
+    security(("Bearer" = [])),
+)]
+pub async fn import_graph(
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(payload): Json<Value>,
+) -> ResponseResult<CreateGraphResponse> {
+    let bundle = GraphBundle::from_value(payload).map_err(|e| match e {
+        Error::UnsupportedBundleVersion(_) => (
+            StatusCode::BAD_REQUEST,
+            Json(error_code::GRAPH_BUNDLE_VERSION_UNSUPPORTED.into()),
+        ),
+        Error::SerdeError(e) => {
+            let error = CommonError {
+                code: error_code::INVALID_GRAPH_BUNDLE.0,
+                message: e.to_string(),
+            };
+            (StatusCode::BAD_REQUEST, Json(error))
+        }
+        e @ Error::InvalidBundleVersion(_) => {
+            let error = CommonError {
+                code: error_code::INVALID_GRAPH_BUNDLE.0,
+                message: e.to_string(),
+            };
+            (StatusCode::BAD_REQUEST, Json(error))
+        }
+        _ => (
+            StatusCode::BAD_REQUEST,
+            Json(error_code::INVALID_GRAPH_BUNDLE.into()),
+        ),
+    })?;
+
+    ensure_known_node_kinds(&bundle.graph.nodes)?;
+    ensure_valid_nodes(&bundle.graph.nodes)?;
+
+    let input = GraphInput::from_bundle(&user_id, bundle.graph);
+    let graph = insert_graph(input).await.map_err(|_e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(error_code::SERVER_ERROR.into()),
+        )
+    })?;
+
+    let revision = GraphRevisionInput::new(
+        &graph.uuid,
+        &graph.nodes,
+        &graph.edges,
+        &graph.start_node,
+        &graph.end_node,
+        &user_id,
+        Some("imported from bundle"),
+    );
+    create_graph_revision(revision).await.map_err(|_e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(error_code::SERVER_ERROR.into()),
+        )
+    })?;
+
+    let res = CreateGraphResponse { id: graph.uuid }
+        .into_common_response()
+        .to_json();
+    Ok(res)
+}
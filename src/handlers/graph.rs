@@ -1,27 +1,68 @@
-use axum::{Extension, Json, http::StatusCode};
+use axum::{
+    Extension, Json,
+    extract::Query,
+    http::{HeaderMap, StatusCode},
+};
 use service_utils_rs::services::http::{
     CommonError, CommonResponse, IntoCommonResponse,
     middleware::auth_mw::UserId,
     response::{CommonOk, Empty, ResponseResult},
 };
+use validator::Validate;
 
 use crate::{
-    database::graph::{
-        default_with_owner, delete_graph_by_id, get_graph_by_id, get_graphs_by_owner,
-        update_graph_by_id,
+    database::{
+        graph::{
+            GraphInput, MAX_PAGE_LIMIT, get_graph_by_id, get_graphs_by_owner, insert_graph,
+            patch_graph_by_id, trash_graph_by_id, update_graph_by_id,
+        },
+        revision::{GraphRevisionInput, create_graph_revision, ensure_head_revision},
     },
     error::error_code,
-    models::graph::{
-        CreateGraphRequest, CreateGraphResponse, GraphDetail, GraphSummary, UpdateGraphRequest,
+    handlers::{ApiError, ApiResult, CheckedJson, ensure_known_node_kinds, get_authorized_graph},
+    models::{
+        acl::GraphPermission,
+        graph::{
+            CloneGraphRequest, CreateGraphRequest, CreateGraphResponse, GraphDetail, GraphPage,
+            GraphVersion, ListGraphsQuery, PatchGraphRequest, UpdateGraphQuery, UpdateGraphRequest,
+            VersionConflict,
+        },
+        prompt::{PromptAnalysis, PromptAnalysisRequest},
+        template::GraphTemplateSummary,
+        validation::{GraphValidationReport, ValidateGraphRequest, ValidationFailure},
+    },
+    utils::{
+        headers::if_match_version,
+        prompt::analyze_prompt_nodes,
+        template::{GRAPH_TEMPLATES, GraphTemplate, find_template},
+        validation::validate_graph_structure,
     },
 };
 
+/// 条件更新未命中时重新读取当前版本，返回给客户端用于重新合并
+pub(crate) async fn version_conflict(id: &str) -> ApiError {
+    match get_graph_by_id(id).await {
+        Ok(Some(g)) => ApiError::Conflict(GraphVersion {
+            version: g.updated_at,
+        }),
+        Ok(None) => ApiError::Common((
+            StatusCode::NOT_FOUND,
+            Json(error_code::GRAPH_NOT_FOUND.into()),
+        )),
+        Err(_e) => ApiError::Common((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(error_code::SERVER_ERROR.into()),
+        )),
+    }
+}
+
 #[utoipa::path(
     post,
     path = "/create",
     request_body = CreateGraphRequest,
     responses(
         (status = 200, description = "Succeed", body = CommonResponse<CreateGraphResponse>),
+        (status = 400, description = "Invalid params or unknown template", body = CommonError),
         (status = 500, description = "Error", body = CommonError)
     ),
     description = "创建图表",
@@ -40,16 +81,47 @@ pub async fn create_graph(
         ));
     }
 
-    let graph_id = default_with_owner(&user_id, &payload.name, &payload.description)
-        .await
-        .map_err(|_e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(error_code::SERVER_ERROR.into()),
-            )
-        })?;
+    let (input, message) = match payload.template.as_deref() {
+        Some(template_id) => {
+            let template = find_template(template_id).ok_or((
+                StatusCode::BAD_REQUEST,
+                Json(error_code::GRAPH_TEMPLATE_NOT_FOUND.into()),
+            ))?;
+            let input =
+                GraphInput::from_template(&user_id, &payload.name, &payload.description, template);
+            (input, format!("created from template {}", template_id))
+        }
+        None => {
+            let input =
+                GraphInput::default_with_owner(&user_id, &payload.name, &payload.description);
+            (input, "created".to_string())
+        }
+    };
+    let graph = insert_graph(input).await.map_err(|_e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(error_code::SERVER_ERROR.into()),
+        )
+    })?;
 
-    let res = CreateGraphResponse { id: graph_id }
+    // 创建时的内容作为第一个历史版本
+    let revision = GraphRevisionInput::new(
+        &graph.uuid,
+        &graph.nodes,
+        &graph.edges,
+        &graph.start_node,
+        &graph.end_node,
+        &user_id,
+        Some(message.as_str()),
+    );
+    create_graph_revision(revision).await.map_err(|_e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(error_code::SERVER_ERROR.into()),
+        )
+    })?;
+
+    let res = CreateGraphResponse { id: graph.uuid }
         .into_common_response()
         .to_json();
     Ok(res)
@@ -58,8 +130,9 @@ pub async fn create_graph(
 #[utoipa::path(
     get,
     path = "/get",
+    params(ListGraphsQuery),
     responses(
-        (status = 200, description = "Succeed", body = CommonResponse<Vec<GraphSummary>>),
+        (status = 200, description = "Succeed", body = CommonResponse<GraphPage>),
         (status = 500, description = "Error", body = CommonError)
     ),
     description = "获取图表列表",
@@ -68,14 +141,21 @@ pub async fn create_graph(
 )]
 pub async fn get_graphs(
     Extension(UserId(user_id)): Extension<UserId>,
-) -> ResponseResult<Vec<GraphSummary>> {
-    let graphs = get_graphs_by_owner(&user_id).await.map_err(|_e| {
+    Query(query): Query<ListGraphsQuery>,
+) -> ResponseResult<GraphPage> {
+    let (items, total) = get_graphs_by_owner(&user_id, &query).await.map_err(|_e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(error_code::SERVER_ERROR.into()),
         )
     })?;
-    let res = graphs.into_common_response().to_json();
+    let page = GraphPage {
+        items,
+        total,
+        offset: query.offset,
+        limit: query.limit.clamp(1, MAX_PAGE_LIMIT),
+    };
+    let res = page.into_common_response().to_json();
     Ok(res)
 }
 
@@ -95,23 +175,9 @@ pub async fn get_graph(
     Extension(UserId(user_id)): Extension<UserId>,
     axum::extract::Path(id): axum::extract::Path<String>,
 ) -> ResponseResult<GraphDetail> {
-    let graph = get_graph_by_id(&id).await.map_err(|_e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(error_code::SERVER_ERROR.into()),
-        )
-    })?;
-
-    match graph {
-        Some(g) if g.owner == user_id => {
-            let g: GraphDetail = g.into();
-            Ok(g.into_common_response().to_json())
-        }
-        _ => Err((
-            StatusCode::NOT_FOUND,
-            Json(error_code::GRAPH_NOT_FOUND.into()),
-        )),
-    }
+    let (g, _) = get_authorized_graph(&id, &user_id, GraphPermission::View).await?;
+    let g: GraphDetail = g.into();
+    Ok(g.into_common_response().to_json())
 }
 
 #[utoipa::path(
@@ -122,7 +188,7 @@ pub async fn get_graph(
         (status = 404, description = "Graph not found", body = CommonError),
         (status = 500, description = "Error", body = CommonError)
     ),
-    description = "删除图表",
+    description = "删除图表（移入回收站）",
     tag = "Graph",
     security(("Bearer" = [])),
 )]
@@ -130,40 +196,32 @@ pub async fn delete_graph(
     Extension(UserId(user_id)): Extension<UserId>,
     axum::extract::Path(id): axum::extract::Path<String>,
 ) -> ResponseResult<Empty> {
-    let graph = get_graph_by_id(&id).await.map_err(|_e| {
+    get_authorized_graph(&id, &user_id, GraphPermission::Manage).await?;
+
+    // 只做软删除，永久删除通过回收站的 purge 接口或自动清理完成
+    trash_graph_by_id(&id).await.map_err(|_e| {
         (
-            StatusCode::NOT_FOUND,
-            Json(error_code::GRAPH_NOT_FOUND.into()),
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(error_code::SERVER_ERROR.into()),
         )
     })?;
-
-    match graph {
-        Some(g) if g.owner == user_id => {
-            // Call the database function to delete the graph
-            // Assuming a function `delete_graph_by_id` exists
-            delete_graph_by_id(&id).await.map_err(|_e| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(error_code::SERVER_ERROR.into()),
-                )
-            })?;
-            Ok(CommonOk::default().to_json())
-        }
-        _ => Err((
-            StatusCode::FORBIDDEN,
-            Json(error_code::GRAPH_NOT_BELONG_TO_USER.into()),
-        )),
-    }
+    Ok(CommonOk::default().to_json())
 }
 
 // 写一个update函数
 #[utoipa::path(
     put,
     path = "/update/{id}",
+    params(
+        UpdateGraphQuery,
+        ("If-Match" = Option<String>, Header, description = "获取图表时返回的版本号"),
+    ),
     request_body = UpdateGraphRequest,
     responses(
-        (status = 200, description = "Succeed", body = CommonResponse<Empty>),
+        (status = 200, description = "Succeed", body = CommonResponse<GraphVersion>),
+        (status = 400, description = "Graph validation failed in strict mode", body = ValidationFailure),
         (status = 404, description = "Graph not found", body = CommonError),
+        (status = 409, description = "Graph version conflict", body = VersionConflict),
         (status = 500, description = "Error", body = CommonError)
     ),
     description = "更新图表",
@@ -173,35 +231,292 @@ pub async fn delete_graph(
 pub async fn update_graph(
     Extension(UserId(user_id)): Extension<UserId>,
     axum::extract::Path(id): axum::extract::Path<String>,
-    Json(payload): Json<UpdateGraphRequest>,
-) -> ResponseResult<Empty> {
-    // First, fetch the graph and check ownership
-    let graph = get_graph_by_id(&id).await.map_err(|_e| {
+    Query(query): Query<UpdateGraphQuery>,
+    headers: HeaderMap,
+    CheckedJson(payload): CheckedJson<UpdateGraphRequest>,
+) -> ApiResult<GraphVersion> {
+    // 先读取图表并校验编辑权限
+    let (g, _) = get_authorized_graph(&id, &user_id, GraphPermission::Edit).await?;
+    ensure_known_node_kinds(&payload.nodes)?;
+
+    if query.strict {
+        let issues =
+            validate_graph_structure(&payload.nodes, &payload.edges, &g.start_node, &g.end_node);
+        let report = GraphValidationReport::new(issues);
+        if !report.valid {
+            return Err(ApiError::Validation(report));
+        }
+    }
+
+    let version = if_match_version(&headers);
+    if version.as_ref().is_some_and(|v| *v != g.updated_at) {
+        return Err(version_conflict(&id).await);
+    }
+
+    ensure_head_revision(&g).await.map_err(|_e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(error_code::SERVER_ERROR.into()),
         )
     })?;
+    let updated = update_graph_by_id(&id, &payload.nodes, &payload.edges, version.as_deref())
+        .await
+        .map_err(|_e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(error_code::SERVER_ERROR.into()),
+            )
+        })?;
+    let Some(updated) = updated else {
+        return Err(version_conflict(&id).await);
+    };
 
-    match graph {
-        Some(g) if g.owner == user_id => {
-            update_graph_by_id(&id, payload.nodes, payload.edges)
-                .await
-                .map_err(|_e| {
-                    (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(error_code::SERVER_ERROR.into()),
-                    )
-                })?;
-            Ok(CommonOk::default().to_json())
-        }
-        Some(_) => Err((
-            StatusCode::FORBIDDEN,
-            Json(error_code::GRAPH_NOT_BELONG_TO_USER.into()),
-        )),
-        None => Err((
-            StatusCode::NOT_FOUND,
-            Json(error_code::GRAPH_NOT_FOUND.into()),
-        )),
+    // 每次保存都记录一个不可变的历史版本
+    let revision = GraphRevisionInput::new(
+        &id,
+        &payload.nodes,
+        &payload.edges,
+        &g.start_node,
+        &g.end_node,
+        &user_id,
+        payload.message.as_deref(),
+    );
+    create_graph_revision(revision).await.map_err(|_e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(error_code::SERVER_ERROR.into()),
+        )
+    })?;
+
+    let res = GraphVersion {
+        version: updated.updated_at,
     }
+    .into_common_response()
+    .to_json();
+    Ok(res)
+}
+
+#[utoipa::path(
+    post,
+    path = "/validate",
+    request_body = ValidateGraphRequest,
+    responses(
+        (status = 200, description = "Succeed", body = CommonResponse<GraphValidationReport>),
+        (status = 400, description = "Invalid node payload", body = CommonError),
+        (status = 500, description = "Error", body = CommonError)
+    ),
+    description = "校验图表结构",
+    tag = "Graph",
+    security(("Bearer" = [])),
+)]
+pub async fn validate_graph(
+    Extension(UserId(_user_id)): Extension<UserId>,
+    CheckedJson(payload): CheckedJson<ValidateGraphRequest>,
+) -> ResponseResult<GraphValidationReport> {
+    let issues = validate_graph_structure(
+        &payload.nodes,
+        &payload.edges,
+        &payload.start_node,
+        &payload.end_node,
+    );
+    let res = GraphValidationReport::new(issues)
+        .into_common_response()
+        .to_json();
+    Ok(res)
+}
+
+#[utoipa::path(
+    post,
+    path = "/prompt/analyze",
+    request_body = PromptAnalysisRequest,
+    responses(
+        (status = 200, description = "Succeed", body = CommonResponse<Vec<PromptAnalysis>>),
+        (status = 400, description = "Invalid node payload", body = CommonError),
+    ),
+    description = "分析 prompt 节点模板中的变量与上游输入是否对应",
+    tag = "Graph",
+    security(("Bearer" = [])),
+)]
+pub async fn analyze_prompts(
+    Extension(UserId(_user_id)): Extension<UserId>,
+    CheckedJson(payload): CheckedJson<PromptAnalysisRequest>,
+) -> ResponseResult<Vec<PromptAnalysis>> {
+    let analyses = analyze_prompt_nodes(&payload.nodes, &payload.edges);
+    Ok(analyses.into_common_response().to_json())
+}
+
+#[utoipa::path(
+    patch,
+    path = "/patch/{id}",
+    params(
+        ("If-Match" = Option<String>, Header, description = "获取图表时返回的版本号"),
+    ),
+    request_body = PatchGraphRequest,
+    responses(
+        (status = 200, description = "Succeed", body = CommonResponse<GraphVersion>),
+        (status = 400, description = "Invalid params or node not found", body = CommonError),
+        (status = 404, description = "Graph not found", body = CommonError),
+        (status = 409, description = "Graph version conflict", body = VersionConflict),
+        (status = 500, description = "Error", body = CommonError)
+    ),
+    description = "部分更新图表的名称、描述、起始节点和结束节点",
+    tag = "Graph",
+    security(("Bearer" = [])),
+)]
+pub async fn patch_graph(
+    Extension(UserId(user_id)): Extension<UserId>,
+    axum::extract::Path(id): axum::extract::Path<String>,
+    headers: HeaderMap,
+    Json(payload): Json<PatchGraphRequest>,
+) -> ApiResult<GraphVersion> {
+    if payload.validate().is_err() {
+        return Err(ApiError::Common((
+            StatusCode::BAD_REQUEST,
+            Json(error_code::INVALID_PARAMS.into()),
+        )));
+    }
+
+    let (g, _) = get_authorized_graph(&id, &user_id, GraphPermission::Edit).await?;
+
+    // 新的起止节点必须存在于当前保存的节点列表中
+    let node_exists = |node_id: &String| g.nodes.iter().any(|n| &n.id == node_id);
+    let start_ok = payload.start_node.as_ref().is_none_or(node_exists);
+    let end_ok = payload.end_node.as_ref().is_none_or(node_exists);
+    if !start_ok || !end_ok {
+        return Err(ApiError::Common((
+            StatusCode::BAD_REQUEST,
+            Json(error_code::GRAPH_NODE_NOT_FOUND.into()),
+        )));
+    }
+
+    let version = if_match_version(&headers);
+    if version.as_ref().is_some_and(|v| *v != g.updated_at) {
+        return Err(version_conflict(&id).await);
+    }
+
+    let structure_changed = payload.start_node.is_some() || payload.end_node.is_some();
+    if structure_changed {
+        ensure_head_revision(&g).await.map_err(|_e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(error_code::SERVER_ERROR.into()),
+            )
+        })?;
+    }
+    let updated = patch_graph_by_id(&id, &payload, version.as_deref())
+        .await
+        .map_err(|_e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(error_code::SERVER_ERROR.into()),
+            )
+        })?;
+    let Some(updated) = updated else {
+        return Err(version_conflict(&id).await);
+    };
+
+    // 起止节点属于图的结构，变化时同样记录历史版本
+    if structure_changed {
+        let start_node = payload.start_node.as_deref().unwrap_or(&g.start_node);
+        let end_node = payload.end_node.as_deref().unwrap_or(&g.end_node);
+        let revision = GraphRevisionInput::new(
+            &id, &g.nodes, &g.edges, start_node, end_node, &user_id, None,
+        );
+        create_graph_revision(revision).await.map_err(|_e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(error_code::SERVER_ERROR.into()),
+            )
+        })?;
+    }
+
+    let res = GraphVersion {
+        version: updated.updated_at,
+    }
+    .into_common_response()
+    .to_json();
+    Ok(res)
+}
+
+#[utoipa::path(
+    post,
+    path = "/clone/{id}",
+    request_body(content = Option<CloneGraphRequest>),
+    responses(
+        (status = 200, description = "Succeed", body = CommonResponse<CreateGraphResponse>),
+        (status = 400, description = "Invalid params", body = CommonError),
+        (status = 404, description = "Graph not found", body = CommonError),
+        (status = 500, description = "Error", body = CommonError)
+    ),
+    description = "复制图表到新的 ID",
+    tag = "Graph",
+    security(("Bearer" = [])),
+)]
+pub async fn clone_graph(
+    Extension(UserId(user_id)): Extension<UserId>,
+    axum::extract::Path(id): axum::extract::Path<String>,
+    payload: Option<Json<CloneGraphRequest>>,
+) -> ResponseResult<CreateGraphResponse> {
+    let payload = payload.map(|Json(p)| p).unwrap_or_default();
+    if payload.validate().is_err() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(error_code::INVALID_PARAMS.into()),
+        ));
+    }
+
+    let (source, _) = get_authorized_graph(&id, &user_id, GraphPermission::View).await?;
+
+    // 默认名称需要满足创建时 100 个字符的长度限制
+    let name = payload.name.unwrap_or_else(|| {
+        format!("Copy of {}", source.name)
+            .chars()
+            .take(100)
+            .collect()
+    });
+    let input = GraphInput::clone_from(&user_id, source, name, payload.regenerate_ids);
+    let graph = insert_graph(input).await.map_err(|_e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(error_code::SERVER_ERROR.into()),
+        )
+    })?;
+
+    let message = format!("cloned from graph {}", id);
+    let revision = GraphRevisionInput::new(
+        &graph.uuid,
+        &graph.nodes,
+        &graph.edges,
+        &graph.start_node,
+        &graph.end_node,
+        &user_id,
+        Some(message.as_str()),
+    );
+    create_graph_revision(revision).await.map_err(|_e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(error_code::SERVER_ERROR.into()),
+        )
+    })?;
+
+    let res = CreateGraphResponse { id: graph.uuid }
+        .into_common_response()
+        .to_json();
+    Ok(res)
+}
+
+#[utoipa::path(
+    get,
+    path = "/templates",
+    responses(
+        (status = 200, description = "Succeed", body = CommonResponse<Vec<GraphTemplateSummary>>),
+    ),
+    description = "获取内置图表模板列表",
+    tag = "Graph",
+    security(("Bearer" = [])),
+)]
+pub async fn list_templates() -> ResponseResult<Vec<GraphTemplateSummary>> {
+    let templates: Vec<GraphTemplateSummary> =
+        GRAPH_TEMPLATES.iter().map(GraphTemplate::summary).collect();
+    Ok(templates.into_common_response().to_json())
 }
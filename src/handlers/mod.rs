@@ -1,2 +1,138 @@
+pub mod acl;
+pub mod bundle;
 pub mod graph;
+pub mod node;
+pub mod revision;
+pub mod trash;
 pub mod workflow;
+
+use axum::{
+    Json,
+    extract::{FromRequest, rejection::JsonRejection},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use service_utils_rs::services::http::{CommonError, CommonResponse};
+
+use crate::{
+    database::{acl::get_granted_role, graph::get_graph_by_id},
+    error::error_code,
+    models::{
+        Graph, Node,
+        acl::{GraphPermission, GraphRole},
+        graph::GraphVersion,
+        node::check_nodes,
+        validation::GraphValidationReport,
+    },
+    utils::validation::find_unknown_node_kind,
+};
+
+pub(crate) type HandlerError = (StatusCode, Json<CommonError>);
+
+/// 需要返回结构化校验结果或版本冲突的接口使用的错误类型，其余错误与 HandlerError 一致
+pub(crate) enum ApiError {
+    Common(HandlerError),
+    Validation(GraphValidationReport),
+    Conflict(GraphVersion),
+}
+
+pub(crate) type ApiResult<T> = Result<Json<CommonResponse<T>>, ApiError>;
+
+impl From<HandlerError> for ApiError {
+    fn from(e: HandlerError) -> Self {
+        ApiError::Common(e)
+    }
+}
+
+/// 请求体的 JSON 提取器：反序列化失败（包括节点 payload 检查）时返回
+/// INVALID_PARAMS，错误信息指出具体字段
+#[derive(FromRequest)]
+#[from_request(via(Json), rejection(ApiError))]
+pub(crate) struct CheckedJson<T>(pub T);
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        ApiError::Common(invalid_params(rejection.body_text()))
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        match self {
+            ApiError::Common(e) => e.into_response(),
+            ApiError::Validation(report) => {
+                (StatusCode::BAD_REQUEST, Json(report.into_failure())).into_response()
+            }
+            ApiError::Conflict(current) => {
+                (StatusCode::CONFLICT, Json(current.into_conflict())).into_response()
+            }
+        }
+    }
+}
+
+/// 读取图表并校验当前用户的角色是否具备所需权限：
+/// 没有任何角色时返回 GRAPH_NOT_BELONG_TO_USER，角色权限不足时返回 GRAPH_PERMISSION_DENIED
+pub(crate) async fn get_authorized_graph(
+    id: &str,
+    user_id: &str,
+    permission: GraphPermission,
+) -> Result<(Graph, GraphRole), HandlerError> {
+    let server_error = |_e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(error_code::SERVER_ERROR.into()),
+        )
+    };
+
+    let graph = get_graph_by_id(id).await.map_err(server_error)?.ok_or((
+        StatusCode::NOT_FOUND,
+        Json(error_code::GRAPH_NOT_FOUND.into()),
+    ))?;
+
+    let role = if graph.owner == user_id {
+        Some(GraphRole::Owner)
+    } else {
+        get_granted_role(id, user_id).await.map_err(server_error)?
+    };
+
+    match role {
+        Some(role) if role.allows(permission) => Ok((graph, role)),
+        Some(_) => Err((
+            StatusCode::FORBIDDEN,
+            Json(error_code::GRAPH_PERMISSION_DENIED.into()),
+        )),
+        None => Err((
+            StatusCode::FORBIDDEN,
+            Json(error_code::GRAPH_NOT_BELONG_TO_USER.into()),
+        )),
+    }
+}
+
+/// 拒绝包含未注册节点类型的图表，错误信息中给出具体节点和类型
+pub(crate) fn ensure_known_node_kinds(nodes: &[Node]) -> Result<(), HandlerError> {
+    match find_unknown_node_kind(nodes) {
+        Some(message) => Err((
+            StatusCode::BAD_REQUEST,
+            Json(CommonError {
+                code: error_code::UNKNOWN_NODE_KIND.0,
+                message,
+            }),
+        )),
+        None => Ok(()),
+    }
+}
+
+pub(crate) fn invalid_params(message: String) -> HandlerError {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(CommonError {
+            code: error_code::INVALID_PARAMS.0,
+            message,
+        }),
+    )
+}
+
+/// 检查导入数据中节点的 payload 和处理映射，不合法时返回 INVALID_PARAMS，错误信息指出具体节点和字段
+pub(crate) fn ensure_valid_nodes(nodes: &[Node]) -> Result<(), HandlerError> {
+    check_nodes(nodes).map_err(invalid_params)
+}
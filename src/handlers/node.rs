@@ -0,0 +1,23 @@
+use service_utils_rs::services::http::{
+    CommonResponse, IntoCommonResponse, response::ResponseResult,
+};
+
+use crate::{
+    models::node_type::NodeTypeInfo,
+    utils::node_type::{NODE_TYPES, NodeTypeDef},
+};
+
+#[utoipa::path(
+    get,
+    path = "/types",
+    responses(
+        (status = 200, description = "Succeed", body = CommonResponse<Vec<NodeTypeInfo>>),
+    ),
+    description = "获取支持的节点类型列表",
+    tag = "Node",
+    security(("Bearer" = [])),
+)]
+pub async fn list_node_types() -> ResponseResult<Vec<NodeTypeInfo>> {
+    let types: Vec<NodeTypeInfo> = NODE_TYPES.iter().map(NodeTypeDef::info).collect();
+    Ok(types.into_common_response().to_json())
+}
@@ -0,0 +1,173 @@
+use axum::{
+    Extension, Json,
+    extract::Path,
+    http::{HeaderMap, StatusCode},
+};
+use service_utils_rs::services::http::{
+    CommonError, CommonResponse, IntoCommonResponse, middleware::auth_mw::UserId,
+    response::ResponseResult,
+};
+
+use crate::{
+    database::{
+        graph::replace_graph_content,
+        revision::{
+            GraphRevisionInput, create_graph_revision, ensure_head_revision,
+            get_graph_revision_by_id, get_revisions_by_graph,
+        },
+    },
+    error::error_code,
+    handlers::{ApiResult, HandlerError, get_authorized_graph, graph::version_conflict},
+    models::{
+        acl::GraphPermission,
+        graph::{GraphDetail, GraphVersion, VersionConflict},
+        revision::{GraphRevision, GraphRevisionSummary},
+    },
+    utils::headers::if_match_version,
+};
+
+async fn get_revision(id: &str, revision_id: &str) -> Result<GraphRevision, HandlerError> {
+    get_graph_revision_by_id(id, revision_id)
+        .await
+        .map_err(|_e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(error_code::SERVER_ERROR.into()),
+            )
+        })?
+        .ok_or((
+            StatusCode::NOT_FOUND,
+            Json(error_code::GRAPH_REVISION_NOT_FOUND.into()),
+        ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/revision/list/{id}",
+    responses(
+        (status = 200, description = "Succeed", body = CommonResponse<Vec<GraphRevisionSummary>>),
+        (status = 404, description = "Graph not found", body = CommonError),
+        (status = 500, description = "Error", body = CommonError)
+    ),
+    description = "获取图表历史版本列表",
+    tag = "Graph",
+    security(("Bearer" = [])),
+)]
+pub async fn list_revisions(
+    Extension(UserId(user_id)): Extension<UserId>,
+    Path(id): Path<String>,
+) -> ResponseResult<Vec<GraphRevisionSummary>> {
+    get_authorized_graph(&id, &user_id, GraphPermission::View).await?;
+
+    let revisions = get_revisions_by_graph(&id).await.map_err(|_e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(error_code::SERVER_ERROR.into()),
+        )
+    })?;
+    let res = revisions.into_common_response().to_json();
+    Ok(res)
+}
+
+#[utoipa::path(
+    get,
+    path = "/revision/get/{id}/{revision_id}",
+    responses(
+        (status = 200, description = "Succeed", body = CommonResponse<GraphDetail>),
+        (status = 404, description = "Graph or revision not found", body = CommonError),
+        (status = 500, description = "Error", body = CommonError)
+    ),
+    description = "获取图表历史版本详情",
+    tag = "Graph",
+    security(("Bearer" = [])),
+)]
+pub async fn get_revision_detail(
+    Extension(UserId(user_id)): Extension<UserId>,
+    Path((id, revision_id)): Path<(String, String)>,
+) -> ResponseResult<GraphDetail> {
+    let (graph, _) = get_authorized_graph(&id, &user_id, GraphPermission::View).await?;
+    let revision = get_revision(&id, &revision_id).await?;
+
+    let detail = GraphDetail::from_revision(graph, revision);
+    Ok(detail.into_common_response().to_json())
+}
+
+#[utoipa::path(
+    post,
+    path = "/revision/restore/{id}/{revision_id}",
+    params(
+        ("If-Match" = Option<String>, Header, description = "获取图表时返回的版本号"),
+    ),
+    responses(
+        (status = 200, description = "Succeed", body = CommonResponse<GraphVersion>),
+        (status = 404, description = "Graph or revision not found", body = CommonError),
+        (status = 409, description = "Graph version conflict", body = VersionConflict),
+        (status = 500, description = "Error", body = CommonError)
+    ),
+    description = "将历史版本恢复为当前版本",
+    tag = "Graph",
+    security(("Bearer" = [])),
+)]
+pub async fn restore_revision(
+    Extension(UserId(user_id)): Extension<UserId>,
+    Path((id, revision_id)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> ApiResult<GraphVersion> {
+    let (g, _) = get_authorized_graph(&id, &user_id, GraphPermission::Edit).await?;
+    let revision = get_revision(&id, &revision_id).await?;
+
+    let version = if_match_version(&headers);
+    if version.as_ref().is_some_and(|v| *v != g.updated_at) {
+        return Err(version_conflict(&id).await);
+    }
+
+    ensure_head_revision(&g).await.map_err(|_e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(error_code::SERVER_ERROR.into()),
+        )
+    })?;
+    let updated = replace_graph_content(
+        &id,
+        &revision.nodes,
+        &revision.edges,
+        &revision.start_node,
+        &revision.end_node,
+        version.as_deref(),
+    )
+    .await
+    .map_err(|_e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(error_code::SERVER_ERROR.into()),
+        )
+    })?;
+    let Some(updated) = updated else {
+        return Err(version_conflict(&id).await);
+    };
+
+    // 恢复操作本身也记录为一个新版本，保证历史只追加不修改
+    let message = format!("restored from revision {}", revision.uuid);
+    let input = GraphRevisionInput::new(
+        &id,
+        &revision.nodes,
+        &revision.edges,
+        &revision.start_node,
+        &revision.end_node,
+        &user_id,
+        Some(message.as_str()),
+    );
+    create_graph_revision(input).await.map_err(|_e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(error_code::SERVER_ERROR.into()),
+        )
+    })?;
+
+    let res = GraphVersion {
+        version: updated.updated_at,
+    }
+    .into_common_response()
+    .to_json();
+    Ok(res)
+}
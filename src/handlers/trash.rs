@@ -0,0 +1,139 @@
+use axum::{Extension, Json, extract::Path, http::StatusCode};
+use service_utils_rs::services::http::{
+    CommonError, CommonResponse, IntoCommonResponse,
+    middleware::auth_mw::UserId,
+    response::{CommonOk, Empty, ResponseResult},
+};
+
+use crate::{
+    database::{
+        acl::delete_grants_by_graph,
+        graph::{
+            delete_graph_by_id, get_trashed_graph_by_id, get_trashed_graphs_by_owner,
+            restore_graph_by_id,
+        },
+        revision::delete_revisions_by_graph,
+        run::delete_runs_by_graph,
+    },
+    error::error_code,
+    handlers::HandlerError,
+    models::graph::GraphSummary,
+};
+
+/// 读取回收站中的图表并校验归属
+async fn get_owned_trashed_graph(id: &str, user_id: &str) -> Result<(), HandlerError> {
+    let graph = get_trashed_graph_by_id(id).await.map_err(|_e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(error_code::SERVER_ERROR.into()),
+        )
+    })?;
+
+    match graph {
+        Some(g) if g.owner == user_id => Ok(()),
+        Some(_) => Err((
+            StatusCode::FORBIDDEN,
+            Json(error_code::GRAPH_NOT_BELONG_TO_USER.into()),
+        )),
+        None => Err((
+            StatusCode::NOT_FOUND,
+            Json(error_code::GRAPH_NOT_FOUND.into()),
+        )),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/trash",
+    responses(
+        (status = 200, description = "Succeed", body = CommonResponse<Vec<GraphSummary>>),
+        (status = 500, description = "Error", body = CommonError)
+    ),
+    description = "获取回收站中的图表",
+    tag = "Graph",
+    security(("Bearer" = [])),
+)]
+pub async fn list_trash(
+    Extension(UserId(user_id)): Extension<UserId>,
+) -> ResponseResult<Vec<GraphSummary>> {
+    let graphs = get_trashed_graphs_by_owner(&user_id).await.map_err(|_e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(error_code::SERVER_ERROR.into()),
+        )
+    })?;
+    let res = graphs.into_common_response().to_json();
+    Ok(res)
+}
+
+#[utoipa::path(
+    post,
+    path = "/trash/restore/{id}",
+    responses(
+        (status = 200, description = "Succeed", body = CommonResponse<Empty>),
+        (status = 404, description = "Graph not found in trash", body = CommonError),
+        (status = 500, description = "Error", body = CommonError)
+    ),
+    description = "从回收站恢复图表",
+    tag = "Graph",
+    security(("Bearer" = [])),
+)]
+pub async fn restore_graph(
+    Extension(UserId(user_id)): Extension<UserId>,
+    Path(id): Path<String>,
+) -> ResponseResult<Empty> {
+    get_owned_trashed_graph(&id, &user_id).await?;
+
+    restore_graph_by_id(&id).await.map_err(|_e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(error_code::SERVER_ERROR.into()),
+        )
+    })?;
+    Ok(CommonOk::default().to_json())
+}
+
+#[utoipa::path(
+    delete,
+    path = "/trash/purge/{id}",
+    responses(
+        (status = 200, description = "Succeed", body = CommonResponse<Empty>),
+        (status = 404, description = "Graph not found in trash", body = CommonError),
+        (status = 500, description = "Error", body = CommonError)
+    ),
+    description = "永久删除回收站中的图表",
+    tag = "Graph",
+    security(("Bearer" = [])),
+)]
+pub async fn purge_graph(
+    Extension(UserId(user_id)): Extension<UserId>,
+    Path(id): Path<String>,
+) -> ResponseResult<Empty> {
+    get_owned_trashed_graph(&id, &user_id).await?;
+
+    delete_graph_by_id(&id).await.map_err(|_e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(error_code::SERVER_ERROR.into()),
+        )
+    })?;
+    delete_revisions_by_graph(&id).await.map_err(|_e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(error_code::SERVER_ERROR.into()),
+        )
+    })?;
+    delete_grants_by_graph(&id).await.map_err(|_e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(error_code::SERVER_ERROR.into()),
+        )
+    })?;
+    delete_runs_by_graph(&id).await.map_err(|_e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(error_code::SERVER_ERROR.into()),
+        )
+    })?;
+    Ok(CommonOk::default().to_json())
+}
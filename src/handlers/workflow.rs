@@ -1,24 +1,291 @@
-use axum::{Extension, Json, http::StatusCode};
+use std::{
+    convert::Infallible,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use axum::{
+    Extension, Json,
+    extract::Path,
+    http::StatusCode,
+    response::sse::{Event, KeepAlive, Sse},
+};
+use serde_json::{Value, json};
 use service_utils_rs::services::http::{
-    CommonError, CommonResponse, IntoCommonResponse,
-    middleware::auth_mw::UserId,
-    response::{Empty, ResponseResult},
+    CommonError, CommonResponse, IntoCommonResponse, middleware::auth_mw::UserId,
+    response::ResponseResult,
 };
-use workflow_rs::{Workflow, graph::Graph};
+use surrealdb::Uuid;
+use tokio::sync::mpsc::{self, UnboundedSender};
+use tokio_stream::{Stream, StreamExt, wrappers::UnboundedReceiverStream};
+use tracing::error;
 
 use crate::{
-    database::graph::get_owner_graph_by_id,
+    database::run::{
+        RunRecordInput, create_run_record, finish_run_record, get_run_record, get_runs_by_graph,
+    },
     error::error_code,
-    models::workflow::{RunWorkflowRequest, WorkflowOutput},
+    handlers::{
+        ApiError, ApiResult, CheckedJson, HandlerError, ensure_known_node_kinds,
+        get_authorized_graph, invalid_params,
+    },
+    models::{
+        Edge, Node,
+        acl::GraphPermission,
+        run::{RunRecord, RunRecordSummary, TraceRecorder},
+        validation::{GraphValidationReport, ValidationFailure},
+        workflow::{
+            CreateRunResponse, ExecutionPlan, ExecutionPlanRequest, NodeTestRequest,
+            NodeTestResult, OutputFormat, RunDraftRequest, RunEvent, RunInput, RunState, RunStatus,
+            RunWorkflowRequest, WorkflowOutput,
+        },
+    },
+    services::{
+        executor::{ExecutionError, GraphParts, execute_graph},
+        http_node::HttpNodeError,
+        limits::resolve_limits,
+        runs::{CancelOutcome, cancel_run, get_run, submit},
+        scheduler::{Ticket, enqueue},
+    },
+    settings::RunLimitsCfg,
+    utils::{
+        graph::{apply_start_input, build_node_test_graph},
+        plan::build_execution_plan,
+        validation::validate_graph_structure,
+    },
 };
 
+/// 准备好的一次运行：待执行的图以及写入运行历史所需的快照
+struct PreparedRun {
+    graph_id: String,
+    snapshot: Value,
+    input: Option<Value>,
+    format: Option<OutputFormat>,
+    limits: RunLimitsCfg,
+    parts: GraphParts,
+}
+
+fn resolve_input(input: Option<RunInput>) -> Result<Option<Value>, HandlerError> {
+    input
+        .map(|input| {
+            input.into_payload_input().ok_or((
+                StatusCode::BAD_REQUEST,
+                Json(error_code::INVALID_PARAMS.into()),
+            ))
+        })
+        .transpose()
+}
+
+fn ensure_runnable(
+    nodes: &[Node],
+    edges: &[Edge],
+    start_node: &str,
+    end_node: &str,
+) -> Result<(), ApiError> {
+    let issues = validate_graph_structure(nodes, edges, start_node, end_node);
+    let report = GraphValidationReport::new(issues);
+    if !report.valid {
+        return Err(ApiError::Validation(report));
+    }
+    Ok(())
+}
+
+/// 校验运行权限、应用运行时输入并做结构校验，返回可直接执行的图
+async fn prepare_run(user_id: &str, payload: RunWorkflowRequest) -> Result<PreparedRun, ApiError> {
+    let (mut graph_db, _) =
+        get_authorized_graph(&payload.id, user_id, GraphPermission::Run).await?;
+
+    let input = resolve_input(payload.input)?;
+    if let Some(input) = &input {
+        apply_start_input(&mut graph_db.nodes, &graph_db.start_node, input.clone())
+            .map_err(invalid_params)?;
+    }
+
+    let limits = resolve_limits(payload.limits.as_ref(), graph_db.limits.as_ref());
+    ensure_runnable(
+        &graph_db.nodes,
+        &graph_db.edges,
+        &graph_db.start_node,
+        &graph_db.end_node,
+    )?;
+
+    let snapshot = json!({
+        "nodes": graph_db.nodes,
+        "edges": graph_db.edges,
+        "start_node": graph_db.start_node,
+        "end_node": graph_db.end_node,
+    });
+    Ok(PreparedRun {
+        graph_id: graph_db.uuid,
+        snapshot,
+        input,
+        format: payload.format,
+        limits,
+        parts: GraphParts {
+            nodes: graph_db.nodes,
+            edges: graph_db.edges,
+            start_node: graph_db.start_node,
+            end_node: graph_db.end_node,
+        },
+    })
+}
+
+/// 向调度器申请执行槽，排队已满时返回 429
+fn enqueue_run(user_id: &str) -> Result<Ticket, HandlerError> {
+    enqueue(user_id).map_err(|_| {
+        (
+            StatusCode::TOO_MANY_REQUESTS,
+            Json(error_code::RUN_QUEUE_FULL.into()),
+        )
+    })
+}
+
+fn execution_error(e: ExecutionError) -> HandlerError {
+    match e {
+        ExecutionError::Http(e) => http_node_error(e),
+        ExecutionError::NodeLimit(_) => (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(error_code::RUN_NODE_LIMIT_EXCEEDED.into()),
+        ),
+        ExecutionError::Engine(message) => {
+            let error = CommonError { code: -1, message };
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(error))
+        }
+        ExecutionError::Output => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(error_code::SERVER_ERROR.into()),
+        ),
+    }
+}
+
+fn http_node_error(e: HttpNodeError) -> HandlerError {
+    let (status, code) = match &e {
+        HttpNodeError::UnsupportedUpstream(_)
+        | HttpNodeError::UnsupportedDownstream(_)
+        | HttpNodeError::NotOnPath(_)
+        | HttpNodeError::InCycle(_)
+        | HttpNodeError::InvalidPayload { .. } => {
+            (StatusCode::BAD_REQUEST, error_code::HTTP_NODE_NOT_RUNNABLE)
+        }
+        HttpNodeError::InvalidUrl { .. } | HttpNodeError::HostNotAllowed { .. } => (
+            StatusCode::BAD_REQUEST,
+            error_code::HTTP_NODE_HOST_NOT_ALLOWED,
+        ),
+        _ => (
+            StatusCode::BAD_GATEWAY,
+            error_code::HTTP_NODE_REQUEST_FAILED,
+        ),
+    };
+    let error = CommonError {
+        code: code.0,
+        message: e.to_string(),
+    };
+    (status, Json(error))
+}
+
+async fn execute(
+    parts: GraphParts,
+    format: Option<OutputFormat>,
+    limits: &RunLimitsCfg,
+    events: Option<&UnboundedSender<RunEvent>>,
+) -> Result<WorkflowOutput, HandlerError> {
+    // 包括各段 workflow-rs 执行和 http 请求在内，整体受运行超时约束
+    let timeout = Duration::from_secs(limits.timeout_secs);
+    let run = execute_graph(parts, limits.max_node_executions, events);
+    let output = tokio::time::timeout(timeout, run)
+        .await
+        .map_err(|_elapsed| {
+            (
+                StatusCode::GATEWAY_TIMEOUT,
+                Json(error_code::RUN_TIMEOUT.into()),
+            )
+        })?
+        .map_err(execution_error)?;
+
+    let output = match format {
+        Some(format) => output.into_format(format).ok_or((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(error_code::OUTPUT_FORMAT_MISMATCH.into()),
+        ))?,
+        None => output,
+    };
+
+    let size = serde_json::to_vec(&output).map_or(0, |bytes| bytes.len());
+    if size as u64 > limits.max_output_bytes {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(error_code::RUN_OUTPUT_TOO_LARGE.into()),
+        ));
+    }
+    Ok(output)
+}
+
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+/// 执行并写入运行历史（包括节点级轨迹）；历史写入失败只记录日志，不影响运行结果
+async fn execute_recorded(
+    run_id: String,
+    user_id: &str,
+    prepared: PreparedRun,
+    events: Option<&UnboundedSender<RunEvent>>,
+) -> Result<WorkflowOutput, HandlerError> {
+    let record = RunRecordInput {
+        uuid: run_id.clone(),
+        graph_id: prepared.graph_id,
+        user_id: user_id.to_string(),
+        status: RunStatus::Running,
+        graph: prepared.snapshot,
+        input: prepared.input,
+    };
+    if let Err(e) = create_run_record(record).await {
+        error!("Failed to create run record {}: {}", run_id, e);
+    }
+
+    // 执行器的事件在这里打上时间写入轨迹，再转发给调用方
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let run = async move {
+        let result = execute(prepared.parts, prepared.format, &prepared.limits, Some(&tx)).await;
+        drop(tx);
+        result
+    };
+    let collect = async {
+        let mut recorder = TraceRecorder::default();
+        while let Some(event) = rx.recv().await {
+            recorder.record(&event, unix_millis());
+            if let Some(events) = events {
+                let _ = events.send(event);
+            }
+        }
+        recorder.finish()
+    };
+    let (result, trace) = tokio::join!(run, collect);
+
+    let (status, output, error) = match &result {
+        Ok(output) => (RunStatus::Succeeded, Some(output.clone()), None),
+        Err((_, Json(e))) => (RunStatus::Failed, None, Some(e.message.clone())),
+    };
+    if let Err(e) = finish_run_record(&run_id, status, output, error, trace).await {
+        error!("Failed to finish run record {}: {}", run_id, e);
+    }
+
+    result
+}
+
 #[utoipa::path(
     post,
     path = "/run",
     request_body = RunWorkflowRequest,
     responses(
-        (status = 200, description = "Succeed", body = CommonResponse<Empty>),
+        (status = 200, description = "Succeed", body = CommonResponse<WorkflowOutput>),
+        (status = 400, description = "Invalid input or graph validation failed", body = ValidationFailure),
+        (status = 422, description = "Output format mismatch or run limit exceeded", body = CommonError),
+        (status = 504, description = "Run timed out", body = CommonError),
+        (status = 403, description = "No permission to run graph", body = CommonError),
         (status = 404, description = "Graph not found", body = CommonError),
+        (status = 429, description = "Run queue is full", body = CommonError),
         (status = 500, description = "Error", body = CommonError)
     ),
     description = "运行工作流",
@@ -28,8 +295,193 @@ use crate::{
 pub async fn run_workflow(
     Extension(UserId(user_id)): Extension<UserId>,
     Json(payload): Json<RunWorkflowRequest>,
-) -> ResponseResult<WorkflowOutput> {
-    let graph_db = get_owner_graph_by_id(&payload.id, &user_id)
+) -> ApiResult<WorkflowOutput> {
+    let prepared = prepare_run(&user_id, payload).await?;
+    let ticket = enqueue_run(&user_id)?;
+    let _permit = ticket.wait().await;
+
+    let run_id = Uuid::new_v4().to_string();
+    let output = execute_recorded(run_id, &user_id, prepared, None).await?;
+
+    let res = output.into_common_response().to_json();
+    Ok(res)
+}
+
+#[utoipa::path(
+    post,
+    path = "/run/draft",
+    request_body = RunDraftRequest,
+    responses(
+        (status = 200, description = "Succeed", body = CommonResponse<WorkflowOutput>),
+        (status = 400, description = "Invalid input or graph validation failed", body = ValidationFailure),
+        (status = 422, description = "Output format mismatch or run limit exceeded", body = CommonError),
+        (status = 504, description = "Run timed out", body = CommonError),
+        (status = 429, description = "Run queue is full", body = CommonError),
+        (status = 500, description = "Error", body = CommonError)
+    ),
+    description = "运行未保存的草稿图，不修改已保存的图表",
+    tag = "Workflow",
+    security(("Bearer" = [])),
+)]
+pub async fn run_draft(
+    Extension(UserId(user_id)): Extension<UserId>,
+    CheckedJson(payload): CheckedJson<RunDraftRequest>,
+) -> ApiResult<WorkflowOutput> {
+    let mut nodes = payload.nodes;
+    if let Some(input) = resolve_input(payload.input)? {
+        apply_start_input(&mut nodes, &payload.start_node, input).map_err(invalid_params)?;
+    }
+    let limits = resolve_limits(payload.limits.as_ref(), None);
+    ensure_runnable(
+        &nodes,
+        &payload.edges,
+        &payload.start_node,
+        &payload.end_node,
+    )?;
+
+    let ticket = enqueue_run(&user_id)?;
+    let _permit = ticket.wait().await;
+
+    let parts = GraphParts {
+        nodes,
+        edges: payload.edges,
+        start_node: payload.start_node,
+        end_node: payload.end_node,
+    };
+    let output = execute(parts, payload.format, &limits, None).await?;
+
+    let res = output.into_common_response().to_json();
+    Ok(res)
+}
+
+#[utoipa::path(
+    post,
+    path = "/run/stream",
+    request_body = RunWorkflowRequest,
+    responses(
+        (status = 200, description = "SSE stream of run events", body = RunEvent, content_type = "text/event-stream"),
+        (status = 400, description = "Invalid input or graph validation failed", body = ValidationFailure),
+        (status = 403, description = "No permission to run graph", body = CommonError),
+        (status = 404, description = "Graph not found", body = CommonError),
+        (status = 429, description = "Run queue is full", body = CommonError),
+        (status = 500, description = "Error", body = CommonError)
+    ),
+    description = "以 SSE 流式运行工作流，客户端断开连接后停止运行",
+    tag = "Workflow",
+    security(("Bearer" = [])),
+)]
+pub async fn run_workflow_stream(
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(payload): Json<RunWorkflowRequest>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    // 权限和校验错误仍以普通 JSON 错误返回，通过后才开始推送事件
+    let prepared = prepare_run(&user_id, payload).await?;
+    let ticket = enqueue_run(&user_id)?;
+    let graph_id = prepared.graph_id.clone();
+    let run_id = Uuid::new_v4().to_string();
+
+    let (tx, rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        let run = async {
+            if let Some(position) = ticket.position() {
+                let _ = tx.send(RunEvent::Queued { position });
+            }
+            let _permit = ticket.wait().await;
+            let _ = tx.send(RunEvent::Started {
+                graph_id,
+                node_progress: false,
+            });
+            let event = match execute_recorded(run_id.clone(), &user_id, prepared, Some(&tx)).await
+            {
+                Ok(output) => RunEvent::Finished { output },
+                Err((_, Json(error))) => RunEvent::Failed {
+                    code: error.code,
+                    message: error.message,
+                },
+            };
+            let _ = tx.send(event);
+        };
+
+        // 客户端断开后停止排队或执行，释放执行槽，并把运行记录标记为已取消
+        tokio::select! {
+            biased;
+            _ = run => {}
+            _ = tx.closed() => {
+                if let Err(e) = finish_run_record(&run_id, RunStatus::Cancelled, None, None, Vec::new()).await {
+                    error!("Failed to finish run record {}: {}", run_id, e);
+                }
+            }
+        }
+    });
+
+    let stream = UnboundedReceiverStream::new(rx).map(|event| {
+        let sse_event = Event::default()
+            .event(event.name())
+            .json_data(&event)
+            .unwrap_or_else(|_| Event::default().event(event.name()));
+        Ok(sse_event)
+    });
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+#[utoipa::path(
+    post,
+    path = "/runs",
+    request_body = RunWorkflowRequest,
+    responses(
+        (status = 200, description = "Succeed", body = CommonResponse<CreateRunResponse>),
+        (status = 400, description = "Invalid input or graph validation failed", body = ValidationFailure),
+        (status = 403, description = "No permission to run graph", body = CommonError),
+        (status = 404, description = "Graph not found", body = CommonError),
+        (status = 429, description = "Run queue is full", body = CommonError),
+        (status = 500, description = "Error", body = CommonError)
+    ),
+    description = "异步运行工作流，立即返回运行 ID",
+    tag = "Workflow",
+    security(("Bearer" = [])),
+)]
+pub async fn create_run(
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(payload): Json<RunWorkflowRequest>,
+) -> ApiResult<CreateRunResponse> {
+    let prepared = prepare_run(&user_id, payload).await?;
+    let ticket = enqueue_run(&user_id)?;
+    let queue_position = ticket.position();
+    let graph_id = prepared.graph_id.clone();
+    let id = Uuid::new_v4().to_string();
+
+    let run_id = id.clone();
+    let owner = user_id.clone();
+    let task = async move {
+        execute_recorded(run_id, &owner, prepared, None)
+            .await
+            .map_err(|(_, Json(error))| error.message)
+    };
+    submit(&id, &user_id, &graph_id, ticket, task);
+
+    let res = CreateRunResponse { id, queue_position }
+        .into_common_response()
+        .to_json();
+    Ok(res)
+}
+
+#[utoipa::path(
+    get,
+    path = "/runs/{id}",
+    responses(
+        (status = 200, description = "Succeed", body = CommonResponse<RunState>),
+        (status = 404, description = "Run not found", body = CommonError),
+        (status = 500, description = "Error", body = CommonError)
+    ),
+    description = "查询异步运行的状态和结果，服务重启后从运行历史中读取",
+    tag = "Workflow",
+    security(("Bearer" = [])),
+)]
+pub async fn get_run_status(
+    Extension(UserId(user_id)): Extension<UserId>,
+    Path(id): Path<String>,
+) -> ResponseResult<RunState> {
+    let state = get_run(&id, &user_id)
         .await
         .map_err(|_e| {
             (
@@ -39,33 +491,197 @@ pub async fn run_workflow(
         })?
         .ok_or((
             StatusCode::NOT_FOUND,
-            Json(error_code::GRAPH_NOT_FOUND.into()),
+            Json(error_code::RUN_NOT_FOUND.into()),
         ))?;
+    Ok(state.into_common_response().to_json())
+}
 
-    let graph_data = graph_db.to_graph_data();
-    // println!("Graph data: {:#?}", graph_data);
-    let graph = Graph::from(graph_data);
-    // println!("Graph: {:#?}", graph);
+#[utoipa::path(
+    post,
+    path = "/runs/{id}/cancel",
+    responses(
+        (status = 200, description = "Succeed", body = CommonResponse<RunState>),
+        (status = 404, description = "Run not found", body = CommonError),
+        (status = 409, description = "Run already finished", body = CommonError),
+    ),
+    description = "取消异步运行",
+    tag = "Workflow",
+    security(("Bearer" = [])),
+)]
+pub async fn cancel_run_job(
+    Extension(UserId(user_id)): Extension<UserId>,
+    Path(id): Path<String>,
+) -> ResponseResult<RunState> {
+    match cancel_run(&id, &user_id) {
+        CancelOutcome::Cancelled(state) => {
+            // 后台任务已被中止，不会再写入结束状态，这里补记
+            if let Err(e) =
+                finish_run_record(&id, RunStatus::Cancelled, None, None, Vec::new()).await
+            {
+                error!("Failed to finish run record {}: {}", id, e);
+            }
+            Ok(state.into_common_response().to_json())
+        }
+        CancelOutcome::AlreadyFinished => Err((
+            StatusCode::CONFLICT,
+            Json(error_code::RUN_ALREADY_FINISHED.into()),
+        )),
+        CancelOutcome::NotFound => Err((
+            StatusCode::NOT_FOUND,
+            Json(error_code::RUN_NOT_FOUND.into()),
+        )),
+    }
+}
 
-    let r = Workflow::start(graph).await.map_err(|e| {
-        let error = CommonError {
-            code: -1,
-            message: e.to_string(),
-        };
-        (StatusCode::INTERNAL_SERVER_ERROR, Json(error))
-    })?;
-    // println!("Graph execution result: {:?}", r);
+#[utoipa::path(
+    get,
+    path = "/history/{graph_id}",
+    responses(
+        (status = 200, description = "Succeed", body = CommonResponse<Vec<RunRecordSummary>>),
+        (status = 403, description = "No permission to view graph", body = CommonError),
+        (status = 404, description = "Graph not found", body = CommonError),
+    ),
+    description = "获取图表的运行历史",
+    tag = "Workflow",
+    security(("Bearer" = [])),
+)]
+pub async fn list_run_history(
+    Extension(UserId(user_id)): Extension<UserId>,
+    Path(graph_id): Path<String>,
+) -> ResponseResult<Vec<RunRecordSummary>> {
+    get_authorized_graph(&graph_id, &user_id, GraphPermission::View).await?;
 
-    let r1 = r.as_text().map_err(|_e| {
+    let runs = get_runs_by_graph(&graph_id).await.map_err(|_e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(error_code::SERVER_ERROR.into()),
         )
     })?;
+    Ok(runs.into_common_response().to_json())
+}
 
-    let r1 = WorkflowOutput {
-        output: r1.to_string(),
+#[utoipa::path(
+    get,
+    path = "/history/{graph_id}/{run_id}",
+    responses(
+        (status = 200, description = "Succeed", body = CommonResponse<RunRecord>),
+        (status = 403, description = "No permission to view graph", body = CommonError),
+        (status = 404, description = "Graph or run record not found", body = CommonError),
+    ),
+    description = "获取单次运行的完整记录，包括图快照、输入和输出",
+    tag = "Workflow",
+    security(("Bearer" = [])),
+)]
+pub async fn get_run_history(
+    Extension(UserId(user_id)): Extension<UserId>,
+    Path((graph_id, run_id)): Path<(String, String)>,
+) -> ResponseResult<RunRecord> {
+    get_authorized_graph(&graph_id, &user_id, GraphPermission::View).await?;
+
+    let record = get_run_record(&graph_id, &run_id)
+        .await
+        .map_err(|_e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(error_code::SERVER_ERROR.into()),
+            )
+        })?
+        .ok_or((
+            StatusCode::NOT_FOUND,
+            Json(error_code::RUN_RECORD_NOT_FOUND.into()),
+        ))?;
+    Ok(record.into_common_response().to_json())
+}
+
+#[utoipa::path(
+    post,
+    path = "/node/test",
+    request_body = NodeTestRequest,
+    responses(
+        (status = 200, description = "Succeed; node errors are returned in the result", body = CommonResponse<NodeTestResult>),
+        (status = 400, description = "Invalid input", body = CommonError),
+        (status = 429, description = "Run queue is full", body = CommonError),
+    ),
+    description = "单独运行一个节点，返回其输出或错误",
+    tag = "Workflow",
+    security(("Bearer" = [])),
+)]
+pub async fn test_node(
+    Extension(UserId(user_id)): Extension<UserId>,
+    CheckedJson(payload): CheckedJson<NodeTestRequest>,
+) -> ResponseResult<NodeTestResult> {
+    ensure_known_node_kinds(std::slice::from_ref(&payload.node))?;
+    let input = resolve_input(payload.input)?;
+    let node_id = payload.node.id.clone();
+    let (nodes, edges, start_node, end_node) = build_node_test_graph(payload.node, input);
+
+    let ticket = enqueue_run(&user_id)?;
+    let _permit = ticket.wait().await;
+
+    let limits = resolve_limits(None, None);
+    let parts = GraphParts {
+        nodes,
+        edges,
+        start_node,
+        end_node,
     };
-    let res = r1.into_common_response().to_json();
-    Ok(res)
+    let result = execute(parts, payload.format, &limits, None).await;
+
+    let res = match result {
+        Ok(output) => NodeTestResult {
+            node_id,
+            output: Some(output),
+            error: None,
+        },
+        Err((_, Json(error))) => NodeTestResult {
+            node_id,
+            output: None,
+            error: Some(error),
+        },
+    };
+    Ok(res.into_common_response().to_json())
+}
+
+#[utoipa::path(
+    post,
+    path = "/plan",
+    request_body = ExecutionPlanRequest,
+    responses(
+        (status = 200, description = "Succeed", body = CommonResponse<ExecutionPlan>),
+        (status = 400, description = "Exactly one of id or graph is required, or the inline graph failed validation", body = ValidationFailure),
+        (status = 403, description = "No permission to view graph", body = CommonError),
+        (status = 404, description = "Graph not found", body = CommonError),
+    ),
+    description = "预览执行计划（不会执行任何节点），执行顺序是根据图结构的估计",
+    tag = "Workflow",
+    security(("Bearer" = [])),
+)]
+pub async fn preview_plan(
+    Extension(UserId(user_id)): Extension<UserId>,
+    CheckedJson(payload): CheckedJson<ExecutionPlanRequest>,
+) -> ApiResult<ExecutionPlan> {
+    let (nodes, edges, start_node, end_node) = match (payload.id, payload.graph) {
+        (Some(id), None) => {
+            let (g, _) = get_authorized_graph(&id, &user_id, GraphPermission::View).await?;
+            (g.nodes, g.edges, g.start_node, g.end_node)
+        }
+        (None, Some(g)) => {
+            // 直接提交的图与运行前一样做结构校验
+            let issues = validate_graph_structure(&g.nodes, &g.edges, &g.start_node, &g.end_node);
+            let report = GraphValidationReport::new(issues);
+            if !report.valid {
+                return Err(ApiError::Validation(report));
+            }
+            (g.nodes, g.edges, g.start_node, g.end_node)
+        }
+        _ => {
+            return Err(ApiError::Common((
+                StatusCode::BAD_REQUEST,
+                Json(error_code::INVALID_PARAMS.into()),
+            )));
+        }
+    };
+
+    let plan = build_execution_plan(&nodes, &edges, &start_node, &end_node);
+    Ok(plan.into_common_response().to_json())
 }
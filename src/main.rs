@@ -4,6 +4,7 @@ mod handlers;
 mod logging;
 mod models;
 mod routes;
+mod services;
 mod settings;
 mod utils;
 
@@ -13,7 +14,14 @@ use service_utils_rs::services::{db::init_db, http::http_server, jwt::Jwt};
 use settings::Settings;
 use tracing::{error, info};
 
-use crate::{database::create_tables, logging::init_tracing_to_file};
+use crate::{
+    database::{create_tables, run::fail_orphaned_runs},
+    logging::init_tracing_to_file,
+    services::{
+        http_node::init_http_node, limits::init_run_limits, scheduler::init_scheduler,
+        trash::run_trash_purger,
+    },
+};
 
 #[tokio::main]
 async fn main() {
@@ -23,6 +31,11 @@ async fn main() {
     let settings = Settings::load("config/services.toml").unwrap();
     init_db(settings.surrealdb).await.unwrap();
     create_tables().await.unwrap();
+    fail_orphaned_runs().await.unwrap();
+    tokio::spawn(run_trash_purger(settings.trash));
+    init_run_limits(settings.run_limits);
+    init_scheduler(settings.scheduler).unwrap();
+    init_http_node(settings.http_node);
 
     let jwt = Arc::new(Jwt::new(settings.jwt));
     let router = routes::create_routes(jwt);
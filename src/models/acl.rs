@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphRole {
+    Owner,
+    Editor,
+    Runner,
+    Viewer,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphPermission {
+    /// 查看图表、历史版本，导出和复制
+    View,
+    /// 修改图表内容
+    Edit,
+    /// 运行工作流
+    Run,
+    /// 删除图表、管理共享
+    Manage,
+}
+
+impl GraphRole {
+    pub fn allows(self, permission: GraphPermission) -> bool {
+        match self {
+            GraphRole::Owner => true,
+            GraphRole::Editor => permission != GraphPermission::Manage,
+            GraphRole::Runner => {
+                matches!(permission, GraphPermission::View | GraphPermission::Run)
+            }
+            GraphRole::Viewer => permission == GraphPermission::View,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct GraphGrant {
+    pub graph_id: String,
+    pub user_id: String,
+    pub role: GraphRole,
+    pub granted_by: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+pub struct GrantGraphRequest {
+    #[validate(length(min = 1))]
+    pub user_id: String,
+    /// 只能授予 viewer、editor 或 runner
+    pub role: GraphRole,
+}
@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use utoipa::ToSchema;
+
+use super::Node;
+use crate::{
+    error::{Error, Result},
+    models::{Edge, Graph},
+};
+
+/// 当前导出格式版本，格式变化时递增并在 `MIGRATIONS` 中追加迁移函数
+pub const GRAPH_BUNDLE_FORMAT_VERSION: u64 = 1;
+
+/// `MIGRATIONS[n]` 把版本 n 的数据迁移到版本 n + 1
+const MIGRATIONS: [fn(Value) -> Value; GRAPH_BUNDLE_FORMAT_VERSION as usize] = [migrate_v0_to_v1];
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct BundleGraph {
+    pub name: String,
+    pub description: String,
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+    pub start_node: String,
+    pub end_node: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct GraphBundle {
+    pub format_version: u64,
+    pub graph: BundleGraph,
+}
+
+impl From<Graph> for GraphBundle {
+    fn from(graph: Graph) -> Self {
+        GraphBundle {
+            format_version: GRAPH_BUNDLE_FORMAT_VERSION,
+            graph: BundleGraph {
+                name: graph.name,
+                description: graph.description,
+                nodes: graph.nodes,
+                edges: graph.edges,
+                start_node: graph.start_node,
+                end_node: graph.end_node,
+            },
+        }
+    }
+}
+
+impl GraphBundle {
+    /// 解析任意版本的导出数据，旧版本逐级迁移到当前版本，更新的版本直接拒绝。
+    ///
+    /// 缺少 `format_version` 视为版本 0，存在但不是非负整数（如 `"2"`）时报错
+    pub fn from_value(mut value: Value) -> Result<Self> {
+        let mut version = match value.get("format_version") {
+            None => 0,
+            Some(v) => v
+                .as_u64()
+                .ok_or_else(|| Error::InvalidBundleVersion(v.to_string()))?,
+        };
+        if version > GRAPH_BUNDLE_FORMAT_VERSION {
+            return Err(Error::UnsupportedBundleVersion(version));
+        }
+
+        while version < GRAPH_BUNDLE_FORMAT_VERSION {
+            value = MIGRATIONS[version as usize](value);
+            version += 1;
+        }
+
+        let bundle = serde_json::from_value(value)?;
+        Ok(bundle)
+    }
+}
+
+/// 版本 0 是没有 `format_version` 的原始 `Graph` JSON（手工从数据库复制的数据），
+/// 多余的 uuid、owner、时间戳字段在反序列化时忽略
+fn migrate_v0_to_v1(value: Value) -> Value {
+    json!({
+        "format_version": 1,
+        "graph": value,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph_json() -> Value {
+        json!({
+            "name": "Demo",
+            "description": "A demo graph",
+            "nodes": [Node::new_start_node(), Node::new_llm("llm")],
+            "edges": [Edge::new("start", "llm")],
+            "start_node": "start",
+            "end_node": "llm",
+        })
+    }
+
+    #[test]
+    fn migrates_v0_graph_json() {
+        let mut raw = graph_json();
+        raw["uuid"] = json!("graph-1");
+        raw["owner"] = json!("user-1");
+        raw["created_at"] = json!("2024-01-01T00:00:00Z");
+
+        let bundle = GraphBundle::from_value(raw).unwrap();
+        assert_eq!(bundle.format_version, GRAPH_BUNDLE_FORMAT_VERSION);
+        assert_eq!(bundle.graph.name, "Demo");
+        assert_eq!(bundle.graph.nodes.len(), 2);
+        assert_eq!(bundle.graph.end_node, "llm");
+    }
+
+    #[test]
+    fn current_version_round_trips() {
+        let exported = json!({
+            "format_version": GRAPH_BUNDLE_FORMAT_VERSION,
+            "graph": graph_json(),
+        });
+        let bundle = GraphBundle::from_value(exported.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&bundle).unwrap(), exported);
+    }
+
+    #[test]
+    fn rejects_newer_version() {
+        let bundle = json!({
+            "format_version": GRAPH_BUNDLE_FORMAT_VERSION + 1,
+            "graph": graph_json(),
+        });
+        assert!(matches!(
+            GraphBundle::from_value(bundle),
+            Err(Error::UnsupportedBundleVersion(v)) if v == GRAPH_BUNDLE_FORMAT_VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn rejects_non_integer_version() {
+        for version in [json!("2"), json!(1.5), json!(-1), Value::Null] {
+            let bundle = json!({ "format_version": version, "graph": graph_json() });
+            assert!(matches!(
+                GraphBundle::from_value(bundle),
+                Err(Error::InvalidBundleVersion(_))
+            ));
+        }
+    }
+}
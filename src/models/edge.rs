@@ -23,6 +23,20 @@ pub struct Edge {
     pub animated: bool,
 }
 
+impl Edge {
+    pub fn new(source: &str, target: &str) -> Self {
+        Edge {
+            id: format!("e-{}-{}", source, target),
+            source: source.to_string(),
+            target: target.to_string(),
+            source_handle: None,
+            target_handle: None,
+            marker_end: None,
+            animated: false,
+        }
+    }
+}
+
 impl From<Edge> for workflow_rs::model::graph_data::EdgeData {
     fn from(edge: Edge) -> Self {
         workflow_rs::model::graph_data::EdgeData {
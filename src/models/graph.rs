@@ -1,10 +1,13 @@
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
 use validator::Validate;
 use workflow_rs::model::graph_data::GraphData;
 
-use super::Node;
-use crate::models::Edge;
+use super::{Node, node::deserialize_checked_nodes};
+use crate::{
+    error::error_code,
+    models::{Edge, acl::GraphRole, revision::GraphRevision, workflow::RunLimits},
+};
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct Graph {
@@ -18,6 +21,12 @@ pub struct Graph {
     pub end_node: String,
     pub created_at: String,
     pub updated_at: String,
+    /// 移入回收站的时间，未删除时为空
+    #[serde(default)]
+    pub deleted_at: Option<String>,
+    /// 图表级运行限制
+    #[serde(default)]
+    pub limits: Option<RunLimits>,
 }
 
 #[derive(Debug, Deserialize, ToSchema, Validate)]
@@ -26,6 +35,8 @@ pub struct CreateGraphRequest {
     pub name: String,
     #[validate(length(min = 1, max = 500))]
     pub description: String,
+    /// 内置模板 ID，缺省为空白图表
+    pub template: Option<String>,
 }
 
 #[derive(Debug, Serialize, ToSchema, Validate)]
@@ -38,6 +49,59 @@ pub struct GraphSummary {
     pub uuid: String,
     pub name: String,
     pub description: String,
+    pub node_count: u64,
+    pub created_at: String,
+    pub updated_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<String>,
+    /// 当前用户在该图表上的有效角色
+    pub role: GraphRole,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GraphSortField {
+    Name,
+    CreatedAt,
+    #[default]
+    UpdatedAt,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListGraphsQuery {
+    /// 跳过的条数
+    #[serde(default)]
+    pub offset: u64,
+    /// 每页条数，最大 100
+    #[serde(default = "default_page_limit")]
+    pub limit: u64,
+    #[serde(default)]
+    pub sort: GraphSortField,
+    #[serde(default)]
+    pub order: SortOrder,
+    /// 按名称模糊过滤（不区分大小写）
+    pub name: Option<String>,
+}
+
+fn default_page_limit() -> u64 {
+    20
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct GraphPage {
+    pub items: Vec<GraphSummary>,
+    pub total: u64,
+    pub offset: u64,
+    pub limit: u64,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -47,6 +111,12 @@ pub struct GraphDetail {
     pub description: String,
     pub nodes: Vec<Node>,
     pub edges: Vec<Edge>,
+    pub start_node: String,
+    pub end_node: String,
+    /// 当前版本号，更新时通过 `If-Match` 头回传
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limits: Option<RunLimits>,
 }
 
 impl From<Graph> for GraphDetail {
@@ -57,31 +127,113 @@ impl From<Graph> for GraphDetail {
             description: graph.description,
             nodes: graph.nodes,
             edges: graph.edges,
+            start_node: graph.start_node,
+            end_node: graph.end_node,
+            version: graph.updated_at,
+            limits: graph.limits,
         }
     }
 }
 
-impl Graph {
-    pub fn to_graph_data(self) -> GraphData {
-        GraphData {
-            nodes: self
-                .nodes
-                .into_iter()
-                .map(workflow_rs::model::Node::from)
-                .collect(),
-            edges: self
-                .edges
-                .into_iter()
-                .map(workflow_rs::model::graph_data::EdgeData::from)
-                .collect(),
-            start_node: Some(self.start_node),
-            end_node: Some(self.end_node),
+impl GraphDetail {
+    pub fn from_revision(graph: Graph, revision: GraphRevision) -> Self {
+        GraphDetail {
+            uuid: graph.uuid,
+            name: graph.name,
+            description: graph.description,
+            nodes: revision.nodes,
+            edges: revision.edges,
+            start_node: revision.start_node,
+            end_node: revision.end_node,
+            version: graph.updated_at,
+            limits: graph.limits,
         }
     }
 }
 
+/// 将节点和连线转换为 workflow-rs 的图数据，已保存的图表和草稿共用
+pub fn build_graph_data(
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+    start_node: String,
+    end_node: String,
+) -> GraphData {
+    GraphData {
+        nodes: nodes
+            .into_iter()
+            .map(workflow_rs::model::Node::from)
+            .collect(),
+        edges: edges
+            .into_iter()
+            .map(workflow_rs::model::graph_data::EdgeData::from)
+            .collect(),
+        start_node: Some(start_node),
+        end_node: Some(end_node),
+    }
+}
+
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateGraphRequest {
+    #[serde(deserialize_with = "deserialize_checked_nodes")]
     pub nodes: Vec<Node>,
     pub edges: Vec<Edge>,
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct PatchGraphRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(length(min = 1, max = 100))]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(length(min = 1, max = 500))]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_node: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_node: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limits: Option<RunLimits>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct GraphVersion {
+    pub version: String,
+}
+
+impl GraphVersion {
+    /// 版本冲突时的响应体，当前版本放在 data 中，客户端据此重新合并
+    pub fn into_conflict(self) -> VersionConflict {
+        VersionConflict {
+            code: error_code::GRAPH_VERSION_CONFLICT.0,
+            message: error_code::GRAPH_VERSION_CONFLICT.1.to_string(),
+            data: self,
+        }
+    }
+}
+
+/// 图表版本冲突的错误响应，结构与 CommonResponse 一致
+#[derive(Debug, Serialize, ToSchema)]
+pub struct VersionConflict {
+    pub code: i16,
+    pub message: String,
+    pub data: GraphVersion,
+}
+
+#[derive(Debug, Default, Deserialize, ToSchema, Validate)]
+pub struct CloneGraphRequest {
+    /// 新图表名称，缺省为 "Copy of <原名称>"
+    #[validate(length(min = 1, max = 100))]
+    pub name: Option<String>,
+    /// 是否为节点生成新的 ID（边会同步改写）
+    #[serde(default)]
+    pub regenerate_ids: bool,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct UpdateGraphQuery {
+    /// 严格模式：结构校验存在错误时拒绝保存
+    #[serde(default)]
+    pub strict: bool,
 }
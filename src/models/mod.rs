@@ -1,6 +1,15 @@
+pub mod acl;
+pub mod bundle;
 pub mod edge;
 pub mod graph;
 pub mod node;
+pub mod node_type;
+pub mod payload;
+pub mod prompt;
+pub mod revision;
+pub mod run;
+pub mod template;
+pub mod validation;
 pub mod workflow;
 
 pub use edge::Edge;
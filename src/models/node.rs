@@ -1,9 +1,17 @@
-use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+use serde::{Deserialize, Deserializer, Serialize, de::Error as _};
 use serde_json::{Value, json};
-use utoipa::ToSchema;
+use utoipa::{
+    PartialSchema, ToSchema,
+    openapi::{RefOr, Schema},
+};
 use workflow_rs::model::node::DataProcessorMapping;
 
-use crate::utils::graph::get_workflow_node_type;
+use crate::{
+    models::payload::NodeSchema,
+    utils::{graph::get_workflow_node_type, node_type::check_node_payload},
+};
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct NodeData {
@@ -12,30 +20,99 @@ pub struct NodeData {
     #[serde(default)]
     pub payload: Value,
 }
-#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Node {
     pub id: String,
     #[serde(rename = "type")]
     pub kind: String,
     pub data: NodeData,
     pub position: Position,
+    /// workflow-rs 的数据处理映射（`DataProcessorMapping`），缺省为空映射
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub processors: Option<Value>,
+    /// 显式指定的输入数据 ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_id: Option<String>,
+    /// 显式指定的输出数据 ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_id: Option<String>,
+}
+
+impl PartialSchema for Node {
+    fn schema() -> RefOr<Schema> {
+        NodeSchema::schema()
+    }
+}
+
+impl ToSchema for Node {
+    fn name() -> Cow<'static, str> {
+        Cow::Borrowed("Node")
+    }
+
+    fn schemas(schemas: &mut Vec<(String, RefOr<Schema>)>) {
+        NodeSchema::schemas(schemas);
+    }
+}
+
+/// 检查 `data.payload` 与节点类型匹配，且 `processors` 是合法的处理映射
+fn check_node(node: &Node) -> Result<(), String> {
+    check_node_payload(node)?;
+    node.processor_mapping()
+        .map_err(|e| format!("node '{}': processors: {}", node.id, e))?;
+    Ok(())
+}
+
+/// 逐个检查节点，错误信息指出具体节点和字段
+pub fn check_nodes(nodes: &[Node]) -> Result<(), String> {
+    for (index, node) in nodes.iter().enumerate() {
+        check_node(node).map_err(|e| format!("nodes[{}]: {}", index, e))?;
+    }
+    Ok(())
+}
+
+/// 反序列化请求中的节点列表并逐个检查；已保存的图表读取时不检查，避免历史数据无法加载
+pub fn deserialize_checked_nodes<'de, D>(deserializer: D) -> Result<Vec<Node>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let nodes = Vec::<Node>::deserialize(deserializer)?;
+    check_nodes(&nodes).map_err(D::Error::custom)?;
+    Ok(nodes)
+}
+
+pub fn deserialize_checked_node<'de, D>(deserializer: D) -> Result<Node, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let node = Node::deserialize(deserializer)?;
+    check_node(&node).map_err(D::Error::custom)?;
+    Ok(node)
 }
 
 impl From<Node> for workflow_rs::model::Node {
     fn from(node: Node) -> Self {
         let node_type = get_workflow_node_type(&node.kind);
+        // 请求中的映射已在反序列化时检查过，这里只为历史数据兜底
+        let processors = node.processor_mapping().unwrap_or_default();
         workflow_rs::model::Node {
             id: node.id,
             node_type: node_type,
             data: node.data.payload,
-            processors: DataProcessorMapping::default(),
-            input_id: None,
-            output_id: None,
+            processors,
+            input_id: node.input_id,
+            output_id: node.output_id,
         }
     }
 }
 
 impl Node {
+    pub fn processor_mapping(&self) -> serde_json::Result<DataProcessorMapping> {
+        match &self.processors {
+            Some(processors) => serde_json::from_value(processors.clone()),
+            None => Ok(DataProcessorMapping::default()),
+        }
+    }
+
     pub fn new_start_node() -> Self {
         let data = NodeData {
             label: "Start Node".to_string(),
@@ -55,6 +132,9 @@ impl Node {
             kind: "input".to_string(),
             data: data,
             position: Position::new(0.0, 0.0),
+            processors: None,
+            input_id: None,
+            output_id: None,
         }
     }
 
@@ -69,6 +149,9 @@ impl Node {
             kind: "identity".to_string(),
             data: data,
             position: Position::new(200.0, 0.0),
+            processors: None,
+            input_id: None,
+            output_id: None,
         }
     }
 
@@ -85,6 +168,26 @@ impl Node {
             kind: "prompt".to_string(),
             data: data,
             position: Position::new(100.0, 100.0),
+            processors: None,
+            input_id: None,
+            output_id: None,
+        }
+    }
+
+    pub fn new_llm(id: &str) -> Self {
+        let data = NodeData {
+            label: "LLM Node".to_string(),
+            description: None,
+            payload: json!({}),
+        };
+        Node {
+            id: id.to_string(),
+            kind: "llm".to_string(),
+            data,
+            position: Position::new(100.0, 100.0),
+            processors: None,
+            input_id: None,
+            output_id: None,
         }
     }
 
@@ -101,8 +204,16 @@ impl Node {
             kind: "branch".to_string(),
             data: data,
             position: Position::new(100.0, 100.0),
+            processors: None,
+            input_id: None,
+            output_id: None,
         }
     }
+
+    pub fn with_position(mut self, x: f64, y: f64) -> Self {
+        self.position = Position::new(x, y);
+        self
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -0,0 +1,24 @@
+use serde::Serialize;
+use serde_json::Value;
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Copy, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum NodeCategory {
+    /// 处理数据的节点
+    Data,
+    /// 控制执行流程的节点
+    Control,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct NodeTypeInfo {
+    /// 对应 `Node.type`
+    pub kind: String,
+    pub name: String,
+    pub description: String,
+    pub category: NodeCategory,
+    /// 新建该类型节点时使用的 `payload`
+    #[schema(value_type = Object)]
+    pub default_payload: Value,
+}
@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+
+use super::node::Position;
+
+/// 带类型的数据值，与 workflow-rs 的数据结构一致（如 `{"type": "Single", "value": ...}`）
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TypedValue {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[schema(value_type = Object)]
+    pub value: Value,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct InputPayload {
+    /// 起始输入，运行时可被请求中的输入覆盖
+    pub input: TypedValue,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PromptPayload {
+    /// 提示词模板，`{{input}}` 会被上游的值替换
+    pub template: String,
+}
+
+/// workflow-rs 的 LLM 节点不从 payload 读取参数，模型等配置由引擎自身决定
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct LlmPayload {}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct IdentityPayload {}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct BranchPayload {
+    /// 用于选择分支的问题
+    pub input: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum HttpMethod {
+    #[default]
+    Get,
+    Post,
+    Put,
+    Patch,
+    Delete,
+}
+
+/// 请求体的格式，决定代入 `{{input}}` 时的转义方式和默认的 `Content-Type`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum HttpBodyType {
+    /// 原样代入，`Content-Type: text/plain`
+    #[default]
+    Text,
+    /// 按 JSON 字符串内容转义后代入，模板应写成 `"{{input}}"`，`Content-Type: application/json`
+    Json,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct HttpPayload {
+    #[serde(default)]
+    pub method: HttpMethod,
+    /// URL 模板，`{{input}}` 会被上游的值（URL 编码后）替换；主机必须在配置的白名单中
+    pub url: String,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// 请求体模板，`{{input}}` 会被上游的值替换
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    #[serde(default)]
+    pub body_type: HttpBodyType,
+    /// 从 JSON 响应中取值的 JSON Pointer（如 `/data/answer`），缺省返回整个响应
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extract: Option<String>,
+    /// 超时时间（秒），不能超过配置中的上限
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TypedNodeData<P> {
+    pub label: String,
+    pub description: Option<String>,
+    pub payload: P,
+}
+
+/// 节点的 OpenAPI 描述：按 `type` 区分的联合类型，`data.payload` 的结构由类型决定
+#[derive(ToSchema)]
+#[serde(tag = "type", content = "data", rename_all = "lowercase")]
+#[allow(dead_code)]
+pub(crate) enum NodeBody {
+    Input(TypedNodeData<InputPayload>),
+    Prompt(TypedNodeData<PromptPayload>),
+    Llm(TypedNodeData<LlmPayload>),
+    Identity(TypedNodeData<IdentityPayload>),
+    Branch(TypedNodeData<BranchPayload>),
+    Http(TypedNodeData<HttpPayload>),
+}
+
+#[derive(ToSchema)]
+#[schema(as = Node)]
+#[allow(dead_code)]
+pub(crate) struct NodeSchema {
+    pub id: String,
+    #[serde(flatten)]
+    pub body: NodeBody,
+    pub position: Position,
+    #[schema(value_type = Option<Object>)]
+    pub processors: Option<Value>,
+    pub input_id: Option<String>,
+    pub output_id: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeSet, HashMap};
+
+    use serde_json::json;
+    use utoipa::PartialSchema;
+
+    use super::*;
+    use crate::{models::Node, utils::node_type::NODE_TYPES};
+
+    fn keys(value: &Value) -> BTreeSet<String> {
+        value
+            .as_object()
+            .map(|object| object.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn node_schema_matches_node() {
+        let mut components = Vec::new();
+        NodeSchema::schemas(&mut components);
+        let components: HashMap<String, Value> = components
+            .into_iter()
+            .map(|(name, schema)| (name, serde_json::to_value(schema).unwrap()))
+            .collect();
+        let schema = serde_json::to_value(NodeSchema::schema()).unwrap();
+        let variants = components["NodeBody"]["oneOf"].as_array().unwrap().clone();
+        assert_eq!(variants.len(), NODE_TYPES.len());
+
+        // 顶层字段：NodeSchema 自身的字段加上 NodeBody 展开的 type 和 data
+        let mut fields = keys(&schema["allOf"][1]["properties"]);
+        for variant in &variants {
+            fields.extend(keys(&variant["properties"]));
+        }
+
+        for def in NODE_TYPES {
+            let mut node = Node::new_llm("n1");
+            node.kind = def.kind.to_string();
+            node.data.payload = def.default_payload();
+            node.processors = Some(json!({}));
+            node.input_id = Some("in".to_string());
+            node.output_id = Some("out".to_string());
+            let value = serde_json::to_value(&node).unwrap();
+            assert_eq!(keys(&value), fields, "fields of '{}' node", def.kind);
+
+            let variant = variants
+                .iter()
+                .find(|v| v["properties"]["type"]["enum"][0] == def.kind)
+                .unwrap_or_else(|| panic!("no schema variant for '{}'", def.kind));
+            let data_ref = variant["properties"]["data"]["$ref"].as_str().unwrap();
+            let data_schema = &components[data_ref.rsplit('/').next().unwrap()];
+            assert_eq!(
+                keys(&data_schema["properties"]),
+                keys(&value["data"]),
+                "data of '{}' node",
+                def.kind
+            );
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::{Node, node::deserialize_checked_nodes};
+use crate::models::Edge;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PromptAnalysisRequest {
+    #[serde(deserialize_with = "deserialize_checked_nodes")]
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+}
+
+/// 单个 prompt 节点的模板分析结果
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PromptAnalysis {
+    pub node_id: String,
+    /// 模板中引用的变量，按首次出现的顺序
+    pub variables: Vec<String>,
+    /// 上游连线提供的变量：连线的 `targetHandle`，未设置时为 `input`
+    pub provided: Vec<String>,
+    /// 模板引用但没有上游提供的变量
+    pub undefined: Vec<String>,
+    /// 上游提供但模板没有使用的变量
+    pub unused: Vec<String>,
+    /// 模板语法错误，如未闭合的 `{{`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub syntax_error: Option<String>,
+}
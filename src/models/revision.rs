@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::Node;
+use crate::models::Edge;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct GraphRevision {
+    pub uuid: String,
+    pub graph_id: String,
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+    pub start_node: String,
+    pub end_node: String,
+    pub author: String,
+    pub message: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct GraphRevisionSummary {
+    pub uuid: String,
+    pub graph_id: String,
+    pub author: String,
+    pub message: Option<String>,
+    pub created_at: String,
+}
@@ -0,0 +1,168 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+
+use super::Node;
+use crate::models::{
+    Edge,
+    workflow::{RunEvent, RunState, RunStatus, WorkflowOutput},
+};
+
+/// 运行时使用的图结构快照，之后修改图表不会影响历史记录
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct GraphSnapshot {
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+    pub start_node: String,
+    pub end_node: String,
+}
+
+/// 一步执行的记录：http 节点单独记录；workflow-rs 执行的一段图只能整体计时，
+/// 记在该段的结束节点上，`segment` 列出段内的全部节点
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct NodeTrace {
+    pub node_id: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub segment: Vec<String>,
+    /// 开始时间（Unix 毫秒）
+    pub started_at: u64,
+    /// 结束时间（Unix 毫秒），执行失败或被中止的一步为空
+    pub finished_at: Option<u64>,
+    pub output: Option<WorkflowOutput>,
+}
+
+/// 按执行器推送的事件构建运行轨迹
+#[derive(Debug, Default)]
+pub struct TraceRecorder {
+    steps: Vec<NodeTrace>,
+}
+
+impl TraceRecorder {
+    pub fn record(&mut self, event: &RunEvent, at: u64) {
+        let (node_id, segment) = match event {
+            RunEvent::SegmentStarted { end_node, nodes } => (end_node, nodes.clone()),
+            RunEvent::NodeStarted { node_id } => (node_id, Vec::new()),
+            RunEvent::SegmentFinished { end_node, output }
+            | RunEvent::NodeFinished {
+                node_id: end_node,
+                output,
+            } => {
+                let step = self
+                    .steps
+                    .iter_mut()
+                    .rev()
+                    .find(|s| s.node_id == *end_node && s.finished_at.is_none());
+                if let Some(step) = step {
+                    step.finished_at = Some(at);
+                    step.output = Some(output.clone());
+                }
+                return;
+            }
+            _ => return,
+        };
+        self.steps.push(NodeTrace {
+            node_id: node_id.clone(),
+            segment,
+            started_at: at,
+            finished_at: None,
+            output: None,
+        });
+    }
+
+    pub fn finish(self) -> Vec<NodeTrace> {
+        self.steps
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RunRecord {
+    pub uuid: String,
+    pub graph_id: String,
+    pub user_id: String,
+    pub status: RunStatus,
+    pub graph: GraphSnapshot,
+    #[schema(value_type = Option<Object>)]
+    pub input: Option<Value>,
+    pub output: Option<WorkflowOutput>,
+    pub error: Option<String>,
+    /// 按执行顺序排列的节点级执行记录
+    #[serde(default)]
+    pub trace: Vec<NodeTrace>,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+}
+
+impl From<RunRecord> for RunState {
+    fn from(record: RunRecord) -> Self {
+        RunState {
+            id: record.uuid,
+            graph_id: record.graph_id,
+            status: record.status,
+            output: record.output,
+            error: record.error,
+            queue_position: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RunRecordSummary {
+    pub uuid: String,
+    pub graph_id: String,
+    pub user_id: String,
+    pub status: RunStatus,
+    pub error: Option<String>,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(value: &str) -> WorkflowOutput {
+        WorkflowOutput::Text(value.to_string())
+    }
+
+    #[test]
+    fn records_segments_and_http_nodes() {
+        let mut recorder = TraceRecorder::default();
+        let events = [
+            RunEvent::SegmentStarted {
+                end_node: "llm".to_string(),
+                nodes: vec!["start".to_string(), "llm".to_string()],
+            },
+            RunEvent::SegmentFinished {
+                end_node: "llm".to_string(),
+                output: text("question"),
+            },
+            RunEvent::NodeStarted {
+                node_id: "http".to_string(),
+            },
+            RunEvent::NodeFinished {
+                node_id: "http".to_string(),
+                output: text("answer"),
+            },
+            RunEvent::SegmentStarted {
+                end_node: "end".to_string(),
+                nodes: vec!["http".to_string(), "end".to_string()],
+            },
+        ];
+        for (at, event) in events.iter().enumerate() {
+            recorder.record(event, at as u64 * 10);
+        }
+
+        let trace = recorder.finish();
+        assert_eq!(trace.len(), 3);
+        assert_eq!(trace[0].node_id, "llm");
+        assert_eq!(trace[0].segment, ["start", "llm"]);
+        assert_eq!((trace[0].started_at, trace[0].finished_at), (0, Some(10)));
+        assert_eq!(trace[1].node_id, "http");
+        assert!(trace[1].segment.is_empty());
+        assert_eq!((trace[1].started_at, trace[1].finished_at), (20, Some(30)));
+        assert!(matches!(&trace[1].output, Some(WorkflowOutput::Text(t)) if t == "answer"));
+        // 最后一段未完成（如执行失败），只有开始时间
+        assert_eq!((trace[2].started_at, trace[2].finished_at), (40, None));
+        assert!(trace[2].output.is_none());
+    }
+}
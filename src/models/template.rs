@@ -0,0 +1,9 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct GraphTemplateSummary {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+}
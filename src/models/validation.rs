@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::{Node, node::deserialize_checked_nodes};
+use crate::{error::error_code, models::Edge};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum IssueSeverity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum IssueCode {
+    DuplicateNodeId,
+    DuplicateEdgeId,
+    EdgeSourceNotFound,
+    EdgeTargetNotFound,
+    StartNodeNotFound,
+    EndNodeNotFound,
+    EndNodeUnreachable,
+    UnreachableNode,
+    CycleWithoutBranch,
+    UnknownNodeKind,
+    TemplateSyntaxError,
+    UndefinedTemplateVariable,
+    UnusedTemplateInput,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct GraphIssue {
+    pub severity: IssueSeverity,
+    pub code: IssueCode,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub edge_id: Option<String>,
+    pub message: String,
+}
+
+impl GraphIssue {
+    pub fn node(severity: IssueSeverity, code: IssueCode, node_id: &str, message: String) -> Self {
+        GraphIssue {
+            severity,
+            code,
+            node_id: Some(node_id.to_string()),
+            edge_id: None,
+            message,
+        }
+    }
+
+    pub fn edge(severity: IssueSeverity, code: IssueCode, edge_id: &str, message: String) -> Self {
+        GraphIssue {
+            severity,
+            code,
+            node_id: None,
+            edge_id: Some(edge_id.to_string()),
+            message,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct GraphValidationReport {
+    pub valid: bool,
+    pub issues: Vec<GraphIssue>,
+}
+
+impl GraphValidationReport {
+    pub fn new(issues: Vec<GraphIssue>) -> Self {
+        let valid = !issues
+            .iter()
+            .any(|issue| issue.severity == IssueSeverity::Error);
+        GraphValidationReport { valid, issues }
+    }
+
+    /// 校验失败时的响应体，问题列表原样放在 data 中，前端可直接高亮
+    pub fn into_failure(self) -> ValidationFailure {
+        ValidationFailure {
+            code: error_code::GRAPH_VALIDATION_FAILED.0,
+            message: error_code::GRAPH_VALIDATION_FAILED.1.to_string(),
+            data: self,
+        }
+    }
+}
+
+/// 图结构校验失败的错误响应，结构与 CommonResponse 一致
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ValidationFailure {
+    pub code: i16,
+    pub message: String,
+    pub data: GraphValidationReport,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ValidateGraphRequest {
+    #[serde(deserialize_with = "deserialize_checked_nodes")]
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+    pub start_node: String,
+    pub end_node: String,
+}
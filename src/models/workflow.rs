@@ -1,12 +1,469 @@
 use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use service_utils_rs::services::http::CommonError;
 use utoipa::ToSchema;
+use workflow_rs::OutputData;
+
+use crate::models::{
+    Edge, Node,
+    node::{deserialize_checked_node, deserialize_checked_nodes},
+};
 
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct RunWorkflowRequest {
     pub id: String,
+    /// 本次运行的输入，覆盖起始节点保存的 `payload.input`，不会修改已保存的图表
+    #[serde(default)]
+    pub input: Option<RunInput>,
+    /// 期望的输出格式
+    #[serde(default)]
+    pub format: Option<OutputFormat>,
+    /// 本次运行的限制，覆盖图表和全局配置
+    #[serde(default)]
+    pub limits: Option<RunLimits>,
+}
+
+/// 直接运行未保存的草稿图，不会写入图表或运行历史
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RunDraftRequest {
+    #[serde(deserialize_with = "deserialize_checked_nodes")]
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+    pub start_node: String,
+    pub end_node: String,
+    #[serde(default)]
+    pub input: Option<RunInput>,
+    #[serde(default)]
+    pub format: Option<OutputFormat>,
+    #[serde(default)]
+    pub limits: Option<RunLimits>,
+}
+
+/// 单独运行一个节点，输入作为该节点的上游输出传入
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct NodeTestRequest {
+    #[serde(deserialize_with = "deserialize_checked_node")]
+    pub node: Node,
+    #[serde(default)]
+    pub input: Option<RunInput>,
+    #[serde(default)]
+    pub format: Option<OutputFormat>,
+}
+
+/// 单节点测试结果，节点执行失败时 `error` 中给出原因
+#[derive(Debug, Serialize, ToSchema)]
+pub struct NodeTestResult {
+    pub node_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<WorkflowOutput>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<CommonError>,
+}
+
+/// 预览执行计划：指定已保存图表的 `id`，或直接提供 `graph`
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ExecutionPlanRequest {
+    pub id: Option<String>,
+    pub graph: Option<InlineGraph>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct InlineGraph {
+    #[serde(deserialize_with = "deserialize_checked_nodes")]
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+    pub start_node: String,
+    pub end_node: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ExecutionPlan {
+    pub start_node: String,
+    pub end_node: String,
+    /// 从起始节点出发、按连线拓扑排序估计的执行顺序，不代表引擎实际的调度顺序
+    pub order: Vec<String>,
+    pub reaches_end: bool,
+    pub branches: Vec<BranchPoint>,
+    /// 永远不会执行的节点
+    pub unreachable: Vec<String>,
+    /// 类型未注册的节点（如注册表之前保存的图表），实际运行前会被拒绝
+    pub identity_fallbacks: Vec<IdentityFallback>,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
-pub struct WorkflowOutput {
-    pub output: String,
+pub struct BranchPoint {
+    pub node_id: String,
+    pub paths: Vec<BranchPath>,
+}
+
+/// 分支的一条出边以及选择它之后可能执行到的节点
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BranchPath {
+    pub handle: Option<String>,
+    pub target: String,
+    pub nodes: Vec<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct IdentityFallback {
+    pub node_id: String,
+    pub kind: String,
+}
+
+/// 运行限制，未设置的项沿用上一级（图表或全局配置）的值，且不能超过全局配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct RunLimits {
+    /// 超时时间（秒）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    /// 最多执行的节点数，包括各段交给 workflow-rs 的节点和 http 节点；
+    /// 段内由分支形成的循环无法计数，由超时兜底
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_node_executions: Option<u64>,
+    /// 输出序列化后的最大字节数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output_bytes: Option<u64>,
+}
+
+/// 运行输入：纯文本，或与起始节点相同的带类型结构（如 `{"type": "Single", "value": ...}`）
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(untagged)]
+pub enum RunInput {
+    Text(String),
+    #[schema(value_type = Object)]
+    Typed(Value),
+}
+
+impl RunInput {
+    /// 转换为起始节点 `payload.input` 的结构，带类型的输入必须是包含 `type` 字段的对象
+    pub fn into_payload_input(self) -> Option<Value> {
+        match self {
+            RunInput::Text(text) => Some(json!({
+                "type": "Single",
+                "value": {
+                    "type": "Text",
+                    "value": text
+                }
+            })),
+            RunInput::Typed(value) => value
+                .get("type")
+                .is_some_and(Value::is_string)
+                .then_some(value),
+        }
+    }
+}
+
+/// 工作流输出：与 workflow-rs 的输出值类型一一对应，http 节点的响应按 JSON 类型区分，
+/// 调用方可通过 `format` 转换为其他类型
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", content = "value", rename_all = "lowercase")]
+pub enum WorkflowOutput {
+    /// 文本
+    Text(String),
+    /// JSON 对象
+    #[schema(value_type = Object)]
+    Json(Value),
+    /// 列表，元素为普通 JSON 值
+    #[schema(value_type = Vec<Object>)]
+    List(Vec<Value>),
+    /// 多个输出值，每个值各自带类型
+    #[schema(no_recursion)]
+    Multi(Vec<WorkflowOutput>),
+}
+
+/// 调用方要求的输出格式，不指定时按工作流实际产出返回
+#[derive(Debug, Clone, Copy, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    Text,
+    Json,
+    List,
+    Multi,
+}
+
+impl WorkflowOutput {
+    /// 读取 workflow-rs 的运行结果，无法识别的值类型返回 `None`
+    pub fn from_engine(output: &OutputData) -> Option<Self> {
+        if let Ok(text) = output.as_text() {
+            return Some(Self::Text(text.to_string()));
+        }
+        let value = serde_json::to_value(output).ok()?;
+        Self::from_engine_value(&value)
+    }
+
+    /// 按 workflow-rs 序列化后的结构映射各个值类型，与节点 `payload.input` 的结构一致：
+    /// `{"type": "Single", "value": <值>}` 或 `{"type": "Multi", "value": [<值>, ...]}`，
+    /// 其中的值为 `{"type": "Text" | "Json" | "List", "value": ...}`
+    fn from_engine_value(value: &Value) -> Option<Self> {
+        let inner = value.get("value")?;
+        match value.get("type")?.as_str()? {
+            "Single" => Self::from_engine_value(inner),
+            "Multi" => inner
+                .as_array()?
+                .iter()
+                .map(Self::from_engine_value)
+                .collect::<Option<Vec<_>>>()
+                .map(Self::Multi),
+            "Text" => inner.as_str().map(|text| Self::Text(text.to_string())),
+            "Json" => Some(Self::Json(inner.clone())),
+            "List" => inner.as_array().cloned().map(Self::List),
+            _ => None,
+        }
+    }
+
+    /// 普通 JSON 值（如 http 节点的响应）：字符串为文本，数组为列表，其余为 JSON
+    pub fn from_json(value: Value) -> Self {
+        match value {
+            Value::String(text) => Self::Text(text),
+            Value::Array(items) => Self::List(items),
+            other => Self::Json(other),
+        }
+    }
+
+    /// 作为下一个节点的输入：文本原样使用，其余类型按 JSON 序列化
+    pub fn into_text(self) -> String {
+        match self {
+            Self::Text(text) => text,
+            other => other.into_json().to_string(),
+        }
+    }
+
+    pub fn into_json(self) -> Value {
+        match self {
+            Self::Text(text) => Value::String(text),
+            Self::Json(value) => value,
+            Self::List(items) => Value::Array(items),
+            Self::Multi(items) => Value::Array(items.into_iter().map(Self::into_json).collect()),
+        }
+    }
+
+    /// 转换为指定格式，无法转换时返回 `None`
+    pub fn into_format(self, format: OutputFormat) -> Option<Self> {
+        match (format, self) {
+            (OutputFormat::Text, Self::Text(text)) => Some(Self::Text(text)),
+            (OutputFormat::Text, other) => Some(Self::Text(other.into_json().to_string())),
+            (OutputFormat::Json, Self::Text(text)) => {
+                serde_json::from_str(&text).ok().map(Self::Json)
+            }
+            (OutputFormat::Json, other) => Some(Self::Json(other.into_json())),
+            (OutputFormat::List, Self::List(items)) => Some(Self::List(items)),
+            (OutputFormat::List, Self::Multi(items)) => {
+                Some(Self::List(items.into_iter().map(Self::into_json).collect()))
+            }
+            (OutputFormat::Multi, Self::Multi(items)) => Some(Self::Multi(items)),
+            (OutputFormat::Multi, Self::List(items)) => Some(Self::Multi(
+                items.into_iter().map(Self::from_json).collect(),
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// 流式运行时通过 SSE 推送的事件，事件名与 `type` 字段一致。
+///
+/// workflow-rs 一次执行一段图且只返回结束节点的输出，段内节点的进度和 LLM 的 token 增量
+/// 无法单独上报；http 节点由服务自己执行，按节点上报
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(tag = "type")]
+pub enum RunEvent {
+    /// 没有空闲执行槽时先推送排队位置
+    #[serde(rename = "run_queued")]
+    Queued { position: usize },
+    #[serde(rename = "run_started")]
+    Started {
+        graph_id: String,
+        /// 是否推送段内每个节点的开始、结束事件和 LLM 的 token 增量。
+        /// workflow-rs 的 `Workflow::start` 没有进度回调，目前恒为 `false`，
+        /// 前端应按段（`segment_*`）和 http 节点（`node_*`）展示进度
+        node_progress: bool,
+    },
+    /// 交给 workflow-rs 执行的一段图开始执行，`nodes` 为段内的节点
+    #[serde(rename = "segment_started")]
+    SegmentStarted {
+        end_node: String,
+        nodes: Vec<String>,
+    },
+    /// 一段图执行完成，`output` 为该段结束节点的输出
+    #[serde(rename = "segment_finished")]
+    SegmentFinished {
+        end_node: String,
+        output: WorkflowOutput,
+    },
+    #[serde(rename = "node_started")]
+    NodeStarted { node_id: String },
+    #[serde(rename = "node_finished")]
+    NodeFinished {
+        node_id: String,
+        output: WorkflowOutput,
+    },
+    #[serde(rename = "run_finished")]
+    Finished { output: WorkflowOutput },
+    #[serde(rename = "run_failed")]
+    Failed { code: i16, message: String },
+}
+
+impl RunEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            RunEvent::Queued { .. } => "run_queued",
+            RunEvent::Started { .. } => "run_started",
+            RunEvent::SegmentStarted { .. } => "segment_started",
+            RunEvent::SegmentFinished { .. } => "segment_finished",
+            RunEvent::NodeStarted { .. } => "node_started",
+            RunEvent::NodeFinished { .. } => "node_finished",
+            RunEvent::Finished { .. } => "run_finished",
+            RunEvent::Failed { .. } => "run_failed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RunStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl RunStatus {
+    pub fn is_finished(self) -> bool {
+        matches!(
+            self,
+            RunStatus::Succeeded | RunStatus::Failed | RunStatus::Cancelled
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct RunState {
+    pub id: String,
+    pub graph_id: String,
+    pub status: RunStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<WorkflowOutput>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// 排队中的估算位置（从 1 开始）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub queue_position: Option<usize>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CreateRunResponse {
+    pub id: String,
+    /// 需要排队时的估算位置，立即开始执行时为空
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub queue_position: Option<usize>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn single(kind: &str, value: Value) -> Value {
+        json!({ "type": "Single", "value": { "type": kind, "value": value } })
+    }
+
+    #[test]
+    fn maps_engine_value_types() {
+        let text = WorkflowOutput::from_engine_value(&single("Text", json!("hi")));
+        assert!(matches!(text, Some(WorkflowOutput::Text(t)) if t == "hi"));
+
+        let object = WorkflowOutput::from_engine_value(&single("Json", json!({ "a": 1 })));
+        assert!(matches!(object, Some(WorkflowOutput::Json(v)) if v == json!({ "a": 1 })));
+
+        let list = WorkflowOutput::from_engine_value(&single("List", json!([1, "two"])));
+        assert!(
+            matches!(list, Some(WorkflowOutput::List(items)) if items == [json!(1), json!("two")])
+        );
+
+        let multi = json!({
+            "type": "Multi",
+            "value": [
+                { "type": "Text", "value": "a" },
+                { "type": "Json", "value": { "b": true } },
+            ]
+        });
+        let multi = WorkflowOutput::from_engine_value(&multi).unwrap();
+        assert_eq!(
+            serde_json::to_value(multi).unwrap(),
+            json!({
+                "type": "multi",
+                "value": [
+                    { "type": "text", "value": "a" },
+                    { "type": "json", "value": { "b": true } },
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn request_nodes_are_checked_when_deserialized() {
+        let mut node = serde_json::to_value(Node::new_prompt("p", "{{input}}")).unwrap();
+        node["data"]["payload"] = json!({ "template": 1 });
+
+        let error = serde_json::from_value::<NodeTestRequest>(json!({ "node": node.clone() }))
+            .unwrap_err()
+            .to_string();
+        assert!(
+            error.contains("node 'p' (prompt): data.payload"),
+            "{}",
+            error
+        );
+
+        let draft = json!({ "nodes": [node], "edges": [], "start_node": "p", "end_node": "p" });
+        let error = serde_json::from_value::<RunDraftRequest>(draft)
+            .unwrap_err()
+            .to_string();
+        assert!(error.starts_with("nodes[0]: node 'p'"), "{}", error);
+    }
+
+    #[test]
+    fn rejects_unknown_engine_values() {
+        assert!(WorkflowOutput::from_engine_value(&single("Image", json!("..."))).is_none());
+        assert!(WorkflowOutput::from_engine_value(&single("Text", json!(1))).is_none());
+        assert!(WorkflowOutput::from_engine_value(&json!("plain")).is_none());
+        let multi = json!({ "type": "Multi", "value": [{ "type": "Audio", "value": "" }] });
+        assert!(WorkflowOutput::from_engine_value(&multi).is_none());
+    }
+
+    #[test]
+    fn converts_between_formats() {
+        let text = || WorkflowOutput::Text(r#"{"a":1}"#.to_string());
+        let list = || WorkflowOutput::List(vec![json!("x"), json!({ "y": 2 })]);
+
+        assert!(matches!(
+            text().into_format(OutputFormat::Json),
+            Some(WorkflowOutput::Json(v)) if v == json!({ "a": 1 })
+        ));
+        assert!(
+            WorkflowOutput::Text("not json".to_string())
+                .into_format(OutputFormat::Json)
+                .is_none()
+        );
+        assert!(text().into_format(OutputFormat::List).is_none());
+        assert!(matches!(
+            list().into_format(OutputFormat::Text),
+            Some(WorkflowOutput::Text(t)) if t == r#"["x",{"y":2}]"#
+        ));
+
+        let multi = list().into_format(OutputFormat::Multi).unwrap();
+        assert!(matches!(
+            &multi,
+            WorkflowOutput::Multi(items)
+                if matches!(&items[0], WorkflowOutput::Text(t) if t == "x")
+                    && matches!(&items[1], WorkflowOutput::Json(_))
+        ));
+        assert!(matches!(
+            multi.into_format(OutputFormat::List),
+            Some(WorkflowOutput::List(items)) if items == [json!("x"), json!({ "y": 2 })]
+        ));
+        assert!(
+            WorkflowOutput::Json(json!({}))
+                .into_format(OutputFormat::Multi)
+                .is_none()
+        );
+    }
 }
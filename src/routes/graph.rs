@@ -1,10 +1,19 @@
 use axum::{
     Router,
-    routing::{delete, get, post, put},
+    routing::{delete, get, patch, post, put},
 };
 use utoipa::OpenApi;
 
-use crate::handlers::graph::{create_graph, delete_graph, get_graph, get_graphs, update_graph};
+use crate::handlers::{
+    acl::{grant_graph, list_grants, revoke_graph},
+    bundle::{export_graph, import_graph},
+    graph::{
+        analyze_prompts, clone_graph, create_graph, delete_graph, get_graph, get_graphs,
+        list_templates, patch_graph, update_graph, validate_graph,
+    },
+    revision::{get_revision_detail, list_revisions, restore_revision},
+    trash::{list_trash, purge_graph, restore_graph},
+};
 
 #[derive(OpenApi)]
 #[openapi(
@@ -14,6 +23,22 @@ use crate::handlers::graph::{create_graph, delete_graph, get_graph, get_graphs,
         crate::handlers::graph::get_graph,
         crate::handlers::graph::delete_graph,
         crate::handlers::graph::update_graph,
+        crate::handlers::graph::patch_graph,
+        crate::handlers::graph::validate_graph,
+        crate::handlers::graph::analyze_prompts,
+        crate::handlers::graph::clone_graph,
+        crate::handlers::graph::list_templates,
+        crate::handlers::revision::list_revisions,
+        crate::handlers::revision::get_revision_detail,
+        crate::handlers::revision::restore_revision,
+        crate::handlers::bundle::export_graph,
+        crate::handlers::bundle::import_graph,
+        crate::handlers::trash::list_trash,
+        crate::handlers::trash::restore_graph,
+        crate::handlers::trash::purge_graph,
+        crate::handlers::acl::list_grants,
+        crate::handlers::acl::grant_graph,
+        crate::handlers::acl::revoke_graph,
     ),
     tags(
         (name = "Graph", description = "Graph Management APIs")
@@ -29,4 +54,22 @@ pub fn graph_routes() -> Router {
         .route("/get/{id}", get(get_graph))
         .route("/delete/{id}", delete(delete_graph))
         .route("/update/{id}", put(update_graph))
+        .route("/patch/{id}", patch(patch_graph))
+        .route("/validate", post(validate_graph))
+        .route("/prompt/analyze", post(analyze_prompts))
+        .route("/clone/{id}", post(clone_graph))
+        .route("/templates", get(list_templates))
+        .route("/revision/list/{id}", get(list_revisions))
+        .route("/revision/get/{id}/{revision_id}", get(get_revision_detail))
+        .route(
+            "/revision/restore/{id}/{revision_id}",
+            post(restore_revision),
+        )
+        .route("/export/{id}", get(export_graph))
+        .route("/import", post(import_graph))
+        .route("/trash", get(list_trash))
+        .route("/trash/restore/{id}", post(restore_graph))
+        .route("/trash/purge/{id}", delete(purge_graph))
+        .route("/share/{id}", get(list_grants).post(grant_graph))
+        .route("/share/{id}/{user_id}", delete(revoke_graph))
 }
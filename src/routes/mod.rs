@@ -1,4 +1,5 @@
 mod graph;
+mod node;
 mod workflow;
 
 use std::sync::Arc;
@@ -15,12 +16,16 @@ use utoipa::{
 };
 use utoipa_swagger_ui::SwaggerUi;
 
-use crate::routes::workflow::WorkflowApi;
+use crate::routes::{
+    node::{NodeApi, node_routes},
+    workflow::WorkflowApi,
+};
 
 #[derive(OpenApi)]
 #[openapi(
         nest(
             (path = "/graph", api = GraphApi),
+            (path = "/nodes", api = NodeApi),
             (path = "/workflow", api = WorkflowApi)
         ),
     )]
@@ -43,6 +48,7 @@ pub fn create_routes(jwt: Arc<Jwt>) -> Router {
 
     Router::new()
         .nest("/graph", graph_routes())
+        .nest("/nodes", node_routes())
         .nest("/workflow", workflow::workflow_routes())
         .route_layer(from_fn(auth))
         .layer(Extension(jwt))
@@ -0,0 +1,18 @@
+use axum::{Router, routing::get};
+use utoipa::OpenApi;
+
+use crate::handlers::node::list_node_types;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(crate::handlers::node::list_node_types),
+    tags(
+        (name = "Node", description = "Node Type APIs")
+    ),
+)]
+
+pub struct NodeApi;
+
+pub fn node_routes() -> Router {
+    Router::new().route("/types", get(list_node_types))
+}
@@ -1,12 +1,27 @@
-use axum::{Router, routing::post};
+use axum::{
+    Router,
+    routing::{get, post},
+};
 use utoipa::OpenApi;
 
-use crate::handlers::workflow::run_workflow;
+use crate::handlers::workflow::{
+    cancel_run_job, create_run, get_run_history, get_run_status, list_run_history, preview_plan,
+    run_draft, run_workflow, run_workflow_stream, test_node,
+};
 
 #[derive(OpenApi)]
 #[openapi(
     paths(
-        crate::handlers::workflow::run_workflow
+        crate::handlers::workflow::run_workflow,
+        crate::handlers::workflow::run_draft,
+        crate::handlers::workflow::run_workflow_stream,
+        crate::handlers::workflow::create_run,
+        crate::handlers::workflow::get_run_status,
+        crate::handlers::workflow::cancel_run_job,
+        crate::handlers::workflow::list_run_history,
+        crate::handlers::workflow::get_run_history,
+        crate::handlers::workflow::test_node,
+        crate::handlers::workflow::preview_plan
     ),
     tags(
         (name = "Workflow", description = "Workflow Management APIs")
@@ -16,5 +31,15 @@ use crate::handlers::workflow::run_workflow;
 pub struct WorkflowApi;
 
 pub fn workflow_routes() -> Router {
-    Router::new().route("/run", post(run_workflow))
+    Router::new()
+        .route("/run", post(run_workflow))
+        .route("/run/draft", post(run_draft))
+        .route("/run/stream", post(run_workflow_stream))
+        .route("/runs", post(create_run))
+        .route("/runs/{id}", get(get_run_status))
+        .route("/runs/{id}/cancel", post(cancel_run_job))
+        .route("/history/{graph_id}", get(list_run_history))
+        .route("/history/{graph_id}/{run_id}", get(get_run_history))
+        .route("/node/test", post(test_node))
+        .route("/plan", post(preview_plan))
 }
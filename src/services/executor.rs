@@ -0,0 +1,525 @@
+use std::collections::{HashSet, VecDeque};
+
+use serde_json::json;
+use thiserror::Error;
+use tokio::sync::mpsc::UnboundedSender;
+use workflow_rs::{Workflow, graph::Graph};
+
+use crate::{
+    models::{
+        Edge, Node,
+        graph::build_graph_data,
+        payload::HttpPayload,
+        workflow::{RunEvent, WorkflowOutput},
+    },
+    services::http_node::{HttpNodeError, execute_http_node, input_text},
+};
+
+/// 已校验、待执行的图，执行时才转换为 workflow-rs 的图
+pub struct GraphParts {
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+    pub start_node: String,
+    pub end_node: String,
+}
+
+impl GraphParts {
+    fn into_graph(self) -> Graph {
+        let graph_data = build_graph_data(self.nodes, self.edges, self.start_node, self.end_node);
+        Graph::from(graph_data)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ExecutionError {
+    #[error(transparent)]
+    Http(#[from] HttpNodeError),
+    #[error("{0}")]
+    Engine(String),
+    #[error("run exceeded the limit of {0} node executions")]
+    NodeLimit(u64),
+    #[error("workflow output has an unsupported type")]
+    Output,
+}
+
+/// http 节点把图切开后的三部分
+struct Split {
+    http_node: Node,
+    payload: HttpPayload,
+    /// 以 http 节点唯一的上游节点为终点的上游段；http 节点是起始节点时为空
+    upstream: Option<GraphParts>,
+    /// http 节点之后的下游段，不含 http 节点本身；http 节点是结束节点时为空
+    downstream: Option<GraphParts>,
+}
+
+/// 单次运行剩余可执行的节点数
+struct ExecutionBudget {
+    limit: u64,
+    used: u64,
+}
+
+impl ExecutionBudget {
+    fn new(limit: u64) -> Self {
+        ExecutionBudget { limit, used: 0 }
+    }
+
+    /// 在执行前扣除 `count` 次节点执行，超出上限时不执行
+    fn charge(&mut self, count: usize) -> Result<(), ExecutionError> {
+        self.used += count as u64;
+        if self.used > self.limit {
+            return Err(ExecutionError::NodeLimit(self.limit));
+        }
+        Ok(())
+    }
+}
+
+/// 一段图要执行的节点数；从 http 响应继续执行的段，起始节点只是携带响应的 input 节点，不计入。
+/// 段内按全部节点计数，分支只走其中一条时会多计
+fn segment_executions(parts: &GraphParts, resumed: bool) -> usize {
+    parts.nodes.len() - usize::from(resumed && !parts.nodes.is_empty())
+}
+
+/// 执行图并返回结束节点的输出。
+///
+/// workflow-rs 不支持自定义节点，因此按 http 节点分段执行：先用 workflow-rs 跑到 http 节点的
+/// 上游节点，把它的输出代入请求，再以响应作为下一段的起点继续执行，直到没有 http 节点。
+/// 每段和每个 http 节点执行前都按 `max_node_executions` 扣减，每轮至少执行一个 http 节点，
+/// 因此分段循环的轮数也受该上限约束。
+/// 传入 `events` 时推送每段和每个 http 节点的开始、结束事件
+pub async fn execute_graph(
+    parts: GraphParts,
+    max_node_executions: u64,
+    events: Option<&UnboundedSender<RunEvent>>,
+) -> Result<WorkflowOutput, ExecutionError> {
+    let emit = |event: RunEvent| {
+        if let Some(events) = events {
+            let _ = events.send(event);
+        }
+    };
+
+    let mut budget = ExecutionBudget::new(max_node_executions);
+    let mut parts = parts;
+    let mut resumed = false;
+    loop {
+        let Some(http_id) = next_http_node(&parts)? else {
+            budget.charge(segment_executions(&parts, resumed))?;
+            return run_segment(parts, &emit).await;
+        };
+        let split = split_at(parts, &http_id)?;
+
+        let input = match split.upstream {
+            Some(upstream) => {
+                budget.charge(segment_executions(&upstream, resumed))?;
+                run_segment(upstream, &emit).await?.into_text()
+            }
+            // 起始节点的运行时输入与 input 节点一样写在 payload.input 中
+            None => input_text(&split.http_node.data.payload["input"]),
+        };
+        budget.charge(1)?;
+        emit(RunEvent::NodeStarted {
+            node_id: http_id.clone(),
+        });
+        let response = execute_http_node(&http_id, &split.payload, &input).await?;
+        let output = WorkflowOutput::from_json(response);
+        emit(RunEvent::NodeFinished {
+            node_id: http_id.clone(),
+            output: output.clone(),
+        });
+
+        let Some(mut downstream) = split.downstream else {
+            return Ok(output);
+        };
+        downstream
+            .nodes
+            .push(response_node(split.http_node, output.into_text()));
+        parts = downstream;
+        resumed = true;
+    }
+}
+
+async fn run_segment(
+    parts: GraphParts,
+    emit: &impl Fn(RunEvent),
+) -> Result<WorkflowOutput, ExecutionError> {
+    let end_node = parts.end_node.clone();
+    emit(RunEvent::SegmentStarted {
+        end_node: end_node.clone(),
+        nodes: parts.nodes.iter().map(|n| n.id.clone()).collect(),
+    });
+
+    let output = Workflow::start(parts.into_graph())
+        .await
+        .map_err(|e| ExecutionError::Engine(e.to_string()))?;
+    let output = WorkflowOutput::from_engine(&output).ok_or(ExecutionError::Output)?;
+
+    emit(RunEvent::SegmentFinished {
+        end_node,
+        output: output.clone(),
+    });
+    Ok(output)
+}
+
+/// 找出上游不再有其他 http 节点的 http 节点，全部 http 节点互为上游时说明它们处在环中
+fn next_http_node(parts: &GraphParts) -> Result<Option<String>, HttpNodeError> {
+    let http_ids: Vec<&str> = parts
+        .nodes
+        .iter()
+        .filter(|n| n.kind == "http")
+        .map(|n| n.id.as_str())
+        .collect();
+    let Some(&first) = http_ids.first() else {
+        return Ok(None);
+    };
+
+    for &id in &http_ids {
+        let upstream = reachable(&parts.edges, id, Direction::Upstream);
+        if !http_ids
+            .iter()
+            .any(|&other| other != id && upstream.contains(other))
+        {
+            return Ok(Some(id.to_string()));
+        }
+    }
+    Err(HttpNodeError::InCycle(first.to_string()))
+}
+
+/// 在 http 节点处把图切成上游段和下游段。
+///
+/// http 节点只能有一个上游节点（起始节点则没有），下游段只能依赖 http 节点及其自身的节点，
+/// 这样每个节点只执行一次
+fn split_at(parts: GraphParts, http_id: &str) -> Result<Split, HttpNodeError> {
+    let node_error = || http_id.to_string();
+
+    let incoming: Vec<&Edge> = parts.edges.iter().filter(|e| e.target == http_id).collect();
+    let upstream_end = match incoming.as_slice() {
+        [] if parts.start_node == http_id => None,
+        [edge] if parts.start_node != http_id => Some(edge.source.clone()),
+        _ => return Err(HttpNodeError::UnsupportedUpstream(node_error())),
+    };
+
+    let downstream = reachable(&parts.edges, http_id, Direction::Downstream);
+    if !downstream.contains(parts.end_node.as_str()) {
+        return Err(HttpNodeError::NotOnPath(node_error()));
+    }
+    // 唯一的上游节点也在下游中，说明 http 节点处在环中
+    if upstream_end
+        .as_deref()
+        .is_some_and(|end| downstream.contains(end))
+    {
+        return Err(HttpNodeError::InCycle(node_error()));
+    }
+    for edge in &parts.edges {
+        if edge.target != http_id
+            && downstream.contains(edge.target.as_str())
+            && !downstream.contains(edge.source.as_str())
+        {
+            return Err(HttpNodeError::UnsupportedDownstream(node_error()));
+        }
+    }
+
+    let upstream: HashSet<String> = match &upstream_end {
+        Some(end) => {
+            let ancestors = reachable(&parts.edges, end, Direction::Upstream);
+            if !ancestors.contains(parts.start_node.as_str()) {
+                return Err(HttpNodeError::NotOnPath(node_error()));
+            }
+            let from_start = reachable(&parts.edges, &parts.start_node, Direction::Downstream);
+            ancestors
+                .intersection(&from_start)
+                .map(|id| id.to_string())
+                .collect()
+        }
+        None => HashSet::new(),
+    };
+    let downstream: HashSet<String> = downstream.into_iter().map(str::to_string).collect();
+
+    let mut http_node = None;
+    let (mut upstream_nodes, mut downstream_nodes) = (Vec::new(), Vec::new());
+    for node in parts.nodes {
+        if node.id == http_id {
+            http_node = Some(node);
+        } else if upstream.contains(&node.id) {
+            upstream_nodes.push(node);
+        } else if downstream.contains(&node.id) {
+            downstream_nodes.push(node);
+        }
+    }
+    let (mut upstream_edges, mut downstream_edges) = (Vec::new(), Vec::new());
+    for edge in parts.edges {
+        if upstream.contains(&edge.source) && upstream.contains(&edge.target) {
+            upstream_edges.push(edge);
+        } else if downstream.contains(&edge.source) && downstream.contains(&edge.target) {
+            downstream_edges.push(edge);
+        }
+    }
+
+    let Some(http_node) = http_node else {
+        return Err(HttpNodeError::UnsupportedUpstream(node_error()));
+    };
+    let payload: HttpPayload =
+        serde_json::from_value(http_node.data.payload.clone()).map_err(|e| {
+            HttpNodeError::InvalidPayload {
+                node: node_error(),
+                message: e.to_string(),
+            }
+        })?;
+
+    let upstream = upstream_end.map(|end_node| GraphParts {
+        nodes: upstream_nodes,
+        edges: upstream_edges,
+        start_node: parts.start_node,
+        end_node,
+    });
+    let downstream = (parts.end_node != http_id).then(|| GraphParts {
+        nodes: downstream_nodes,
+        edges: downstream_edges,
+        start_node: http_id.to_string(),
+        end_node: parts.end_node,
+    });
+    Ok(Split {
+        http_node,
+        payload,
+        upstream,
+        downstream,
+    })
+}
+
+/// 下游段的起始节点：沿用 http 节点的 id 和输出 ID，使下游的连线和映射保持不变，
+/// payload 为响应文本
+fn response_node(mut node: Node, text: String) -> Node {
+    node.kind = "input".to_string();
+    node.processors = None;
+    node.input_id = None;
+    node.data.payload = json!({
+        "input": {
+            "type": "Single",
+            "value": {
+                "type": "Text",
+                "value": text
+            }
+        }
+    });
+    node
+}
+
+#[derive(Clone, Copy)]
+enum Direction {
+    Upstream,
+    Downstream,
+}
+
+/// 沿连线方向可达的节点，包含起点本身
+fn reachable<'a>(edges: &'a [Edge], from: &'a str, direction: Direction) -> HashSet<&'a str> {
+    let mut visited = HashSet::from([from]);
+    let mut queue = VecDeque::from([from]);
+    while let Some(id) = queue.pop_front() {
+        for edge in edges {
+            let (near, far) = match direction {
+                Direction::Downstream => (edge.source.as_str(), edge.target.as_str()),
+                Direction::Upstream => (edge.target.as_str(), edge.source.as_str()),
+            };
+            if near == id && visited.insert(far) {
+                queue.push_back(far);
+            }
+        }
+    }
+    visited
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn http_node(id: &str) -> Node {
+        let mut node = Node::new_llm(id);
+        node.kind = "http".to_string();
+        node.data.payload = json!({ "url": "https://example.com/?q={{input}}" });
+        node
+    }
+
+    fn edge(source: &str, target: &str) -> Edge {
+        Edge {
+            id: format!("{source}-{target}"),
+            source: source.to_string(),
+            target: target.to_string(),
+            source_handle: None,
+            target_handle: None,
+            marker_end: None,
+            animated: false,
+        }
+    }
+
+    fn parts(nodes: Vec<Node>, edges: &[(&str, &str)]) -> GraphParts {
+        GraphParts {
+            nodes,
+            edges: edges.iter().map(|(s, t)| edge(s, t)).collect(),
+            start_node: "start".to_string(),
+            end_node: "end".to_string(),
+        }
+    }
+
+    fn ids(parts: &GraphParts) -> Vec<&str> {
+        let mut ids: Vec<&str> = parts.nodes.iter().map(|n| n.id.as_str()).collect();
+        ids.sort();
+        ids
+    }
+
+    /// start -> llm1 -> http -> llm2 -> end
+    fn llm_http_llm() -> GraphParts {
+        parts(
+            vec![
+                Node::new_llm("start"),
+                Node::new_llm("llm1"),
+                http_node("http"),
+                Node::new_llm("llm2"),
+                Node::new_llm("end"),
+            ],
+            &[
+                ("start", "llm1"),
+                ("llm1", "http"),
+                ("http", "llm2"),
+                ("llm2", "end"),
+            ],
+        )
+    }
+
+    #[test]
+    fn splits_between_llm_steps() {
+        let graph = llm_http_llm();
+        assert_eq!(next_http_node(&graph).unwrap().as_deref(), Some("http"));
+
+        let split = split_at(graph, "http").unwrap();
+        let upstream = split.upstream.unwrap();
+        assert_eq!(ids(&upstream), ["llm1", "start"]);
+        assert_eq!(
+            (upstream.start_node.as_str(), upstream.end_node.as_str()),
+            ("start", "llm1")
+        );
+        assert_eq!(upstream.edges.len(), 1);
+
+        let downstream = split.downstream.unwrap();
+        assert_eq!(ids(&downstream), ["end", "llm2"]);
+        assert_eq!(downstream.start_node, "http");
+        assert_eq!(downstream.edges.len(), 2);
+
+        let node = response_node(split.http_node, "response".to_string());
+        assert_eq!(node.kind, "input");
+        assert_eq!(node.data.payload["input"]["value"]["value"], "response");
+    }
+
+    #[test]
+    fn picks_first_http_node_in_chain() {
+        let graph = parts(
+            vec![
+                Node::new_llm("start"),
+                http_node("second"),
+                http_node("first"),
+                Node::new_llm("end"),
+            ],
+            &[("start", "first"), ("first", "second"), ("second", "end")],
+        );
+        assert_eq!(next_http_node(&graph).unwrap().as_deref(), Some("first"));
+    }
+
+    #[test]
+    fn http_end_node_has_no_downstream() {
+        let mut graph = parts(
+            vec![Node::new_llm("start"), http_node("end")],
+            &[("start", "end")],
+        );
+        graph.end_node = "end".to_string();
+        let split = split_at(graph, "end").unwrap();
+        assert!(split.downstream.is_none());
+        assert_eq!(ids(&split.upstream.unwrap()), ["start"]);
+    }
+
+    #[test]
+    fn rejects_downstream_depending_on_other_branches() {
+        // llm2 同时依赖 http 和 http 之外的 side，side 的输出无法带入下游段
+        let graph = parts(
+            vec![
+                Node::new_llm("start"),
+                Node::new_llm("side"),
+                http_node("http"),
+                Node::new_llm("llm2"),
+                Node::new_llm("end"),
+            ],
+            &[
+                ("start", "http"),
+                ("start", "side"),
+                ("side", "llm2"),
+                ("http", "llm2"),
+                ("llm2", "end"),
+            ],
+        );
+        assert!(matches!(
+            split_at(graph, "http"),
+            Err(HttpNodeError::UnsupportedDownstream(_))
+        ));
+    }
+
+    #[test]
+    fn budget_counts_segments_and_http_nodes() {
+        let mut budget = ExecutionBudget::new(6);
+        let graph = llm_http_llm();
+        budget.charge(segment_executions(&graph, false)).unwrap();
+        budget.charge(1).unwrap();
+        assert!(matches!(
+            budget.charge(1),
+            Err(ExecutionError::NodeLimit(6))
+        ));
+
+        // 从 http 响应继续的段不重复计算携带响应的起始节点
+        let split = split_at(llm_http_llm(), "http").unwrap();
+        let mut downstream = split.downstream.unwrap();
+        downstream
+            .nodes
+            .push(response_node(split.http_node, String::new()));
+        assert_eq!(segment_executions(&downstream, true), 2);
+    }
+
+    #[test]
+    fn rejects_multiple_upstreams() {
+        let graph = parts(
+            vec![
+                Node::new_llm("start"),
+                Node::new_llm("a"),
+                http_node("http"),
+                Node::new_llm("end"),
+            ],
+            &[
+                ("start", "a"),
+                ("start", "http"),
+                ("a", "http"),
+                ("http", "end"),
+            ],
+        );
+        assert!(matches!(
+            split_at(graph, "http"),
+            Err(HttpNodeError::UnsupportedUpstream(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_http_node_in_cycle() {
+        // http 唯一的上游 loop 同时也在它的下游
+        let graph = parts(
+            vec![
+                Node::new_llm("start"),
+                Node::new_branch("loop"),
+                http_node("http"),
+                Node::new_llm("end"),
+            ],
+            &[
+                ("start", "loop"),
+                ("loop", "http"),
+                ("http", "loop"),
+                ("loop", "end"),
+            ],
+        );
+        assert!(matches!(
+            split_at(graph, "http"),
+            Err(HttpNodeError::InCycle(_))
+        ));
+    }
+}
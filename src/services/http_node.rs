@@ -0,0 +1,437 @@
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, OnceLock},
+    time::Duration,
+};
+
+use reqwest::{
+    Client, Method, Url,
+    header::{CONTENT_TYPE, HeaderName, HeaderValue},
+    redirect::Policy,
+};
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::{
+    models::payload::{HttpBodyType, HttpMethod, HttpPayload},
+    settings::HttpNodeCfg,
+    utils::prompt::{DEFAULT_TEMPLATE_VARIABLE, render_template},
+};
+
+static CONFIG: OnceLock<HttpNodeCfg> = OnceLock::new();
+
+/// 不跟随重定向，避免绕过主机白名单
+static CLIENT: LazyLock<Client> = LazyLock::new(|| {
+    Client::builder()
+        .redirect(Policy::none())
+        .build()
+        .unwrap_or_default()
+});
+
+/// 启动时写入 http 节点配置
+pub fn init_http_node(cfg: HttpNodeCfg) {
+    let _ = CONFIG.set(cfg);
+}
+
+fn config() -> &'static HttpNodeCfg {
+    static DEFAULT: LazyLock<HttpNodeCfg> = LazyLock::new(HttpNodeCfg::default);
+    CONFIG.get().unwrap_or(&DEFAULT)
+}
+
+#[derive(Debug, Error)]
+pub enum HttpNodeError {
+    #[error("http node '{0}' must have exactly one upstream node, or none as the start node")]
+    UnsupportedUpstream(String),
+    #[error("nodes after http node '{0}' must not depend on nodes outside its downstream")]
+    UnsupportedDownstream(String),
+    #[error("http node '{0}' must lie on a path from the start node to the end node")]
+    NotOnPath(String),
+    #[error("http node '{0}' must not be part of a cycle")]
+    InCycle(String),
+    #[error("http node '{node}': invalid payload: {message}")]
+    InvalidPayload { node: String, message: String },
+    #[error("http node '{node}': invalid url '{url}'")]
+    InvalidUrl { node: String, url: String },
+    #[error("http node '{node}': host '{host}' is not in the allow-list")]
+    HostNotAllowed { node: String, host: String },
+    #[error("http node '{node}': request timed out")]
+    Timeout { node: String },
+    #[error("http node '{node}': request failed: {message}")]
+    Request { node: String, message: String },
+    #[error("http node '{node}': response status {status}")]
+    Status { node: String, status: u16 },
+    #[error("http node '{node}': response exceeded {limit} bytes")]
+    ResponseTooLarge { node: String, limit: u64 },
+    #[error("http node '{node}': nothing found at '{path}' in the response")]
+    ExtractFailed { node: String, path: String },
+}
+
+/// 取出 input 节点的文本值，非文本的值按 JSON 序列化
+pub(crate) fn input_text(input: &Value) -> String {
+    match input.pointer("/value/value") {
+        Some(Value::String(text)) if input["value"]["type"] == "Text" => text.clone(),
+        Some(other) => other.to_string(),
+        None => String::new(),
+    }
+}
+
+/// 按 payload 发出请求，返回响应（JSON 或文本），设置了 `extract` 时返回其指向的部分
+pub async fn execute_http_node(
+    node_id: &str,
+    payload: &HttpPayload,
+    input: &str,
+) -> Result<Value, HttpNodeError> {
+    send_request(config(), node_id, payload, input).await
+}
+
+async fn send_request(
+    cfg: &HttpNodeCfg,
+    node_id: &str,
+    payload: &HttpPayload,
+    input: &str,
+) -> Result<Value, HttpNodeError> {
+    let node = || node_id.to_string();
+
+    let url_vars = HashMap::from([(DEFAULT_TEMPLATE_VARIABLE, percent_encode(input))]);
+    let raw_url = render_template(&payload.url, &url_vars);
+    let url = Url::parse(&raw_url).map_err(|_| HttpNodeError::InvalidUrl {
+        node: node(),
+        url: raw_url.clone(),
+    })?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(HttpNodeError::InvalidUrl {
+            node: node(),
+            url: raw_url,
+        });
+    }
+    let host = url.host_str().unwrap_or_default().to_ascii_lowercase();
+    if !host_allowed(&host, &cfg.allowed_hosts) {
+        return Err(HttpNodeError::HostNotAllowed { node: node(), host });
+    }
+
+    let timeout_secs = payload
+        .timeout_secs
+        .map_or(cfg.timeout_secs, |t| t.min(cfg.timeout_secs))
+        .max(1);
+    let method = match payload.method {
+        HttpMethod::Get => Method::GET,
+        HttpMethod::Post => Method::POST,
+        HttpMethod::Put => Method::PUT,
+        HttpMethod::Patch => Method::PATCH,
+        HttpMethod::Delete => Method::DELETE,
+    };
+    let mut request = CLIENT
+        .request(method, url)
+        .timeout(Duration::from_secs(timeout_secs));
+    for (name, value) in &payload.headers {
+        request = request.header(name, value);
+    }
+    if let Some(body) = &payload.body {
+        let (value, content_type) = match payload.body_type {
+            HttpBodyType::Text => (input.to_string(), "text/plain; charset=utf-8"),
+            HttpBodyType::Json => (json_escape(input), "application/json"),
+        };
+        if !payload
+            .headers
+            .keys()
+            .any(|name| name.eq_ignore_ascii_case(CONTENT_TYPE.as_str()))
+        {
+            request = request.header(CONTENT_TYPE, content_type);
+        }
+        let body_vars = HashMap::from([(DEFAULT_TEMPLATE_VARIABLE, value)]);
+        request = request.body(render_template(body, &body_vars));
+    }
+
+    let request_error = |e: reqwest::Error| {
+        if e.is_timeout() {
+            HttpNodeError::Timeout { node: node() }
+        } else {
+            HttpNodeError::Request {
+                node: node(),
+                message: e.to_string(),
+            }
+        }
+    };
+    let mut response = request.send().await.map_err(request_error)?;
+    if !response.status().is_success() {
+        return Err(HttpNodeError::Status {
+            node: node(),
+            status: response.status().as_u16(),
+        });
+    }
+
+    let limit = cfg.max_response_bytes;
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(request_error)? {
+        body.extend_from_slice(&chunk);
+        if body.len() as u64 > limit {
+            return Err(HttpNodeError::ResponseTooLarge {
+                node: node(),
+                limit,
+            });
+        }
+    }
+
+    let value = serde_json::from_slice(&body)
+        .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&body).into_owned()));
+    match &payload.extract {
+        Some(path) => value
+            .pointer(path)
+            .cloned()
+            .ok_or(HttpNodeError::ExtractFailed {
+                node: node(),
+                path: path.clone(),
+            }),
+        None => Ok(value),
+    }
+}
+
+/// 白名单项可以是完整主机名，或 `*.example.com` 形式匹配其子域名
+fn host_allowed(host: &str, allowed_hosts: &[String]) -> bool {
+    allowed_hosts.iter().any(|allowed| {
+        let allowed = allowed.to_ascii_lowercase();
+        match allowed.strip_prefix("*.") {
+            Some(domain) => host
+                .strip_suffix(domain)
+                .is_some_and(|prefix| prefix.ends_with('.')),
+            None => host == allowed,
+        }
+    })
+}
+
+/// 检查请求头的名称和值是否合法，返回第一个不合法的请求头
+pub(crate) fn check_headers(payload: &HttpPayload) -> Result<(), String> {
+    for (name, value) in &payload.headers {
+        if HeaderName::from_bytes(name.as_bytes()).is_err() {
+            return Err(format!("invalid header name '{}'", name));
+        }
+        if HeaderValue::from_str(value).is_err() {
+            return Err(format!("invalid value for header '{}'", name));
+        }
+    }
+    Ok(())
+}
+
+/// 转义为 JSON 字符串的内容（不含两侧引号）
+fn json_escape(value: &str) -> String {
+    let quoted = Value::String(value.to_string()).to_string();
+    quoted[1 .. quoted.len() - 1].to_string()
+}
+
+/// 代入 URL 的值按 RFC 3986 编码，只保留非保留字符
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A' ..= b'Z' | b'a' ..= b'z' | b'0' ..= b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use axum::{
+        Json, Router,
+        http::{HeaderMap, StatusCode},
+        routing::{get, post},
+    };
+    use serde_json::json;
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    /// 在本地随机端口启动模拟服务，返回其地址
+    async fn mock_server() -> String {
+        let router = Router::new()
+            .route(
+                "/json",
+                get(|| async { Json(json!({ "data": { "answer": "42" } })) }),
+            )
+            .route("/text", get(|| async { "plain text" }))
+            .route("/large", get(|| async { "x".repeat(4096) }))
+            .route(
+                "/slow",
+                get(|| async {
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    "too late"
+                }),
+            )
+            .route(
+                "/echo",
+                post(|headers: HeaderMap, body: String| async move {
+                    let content_type = headers[CONTENT_TYPE].to_str().unwrap().to_string();
+                    Json(json!({ "content_type": content_type, "body": body }))
+                }),
+            )
+            .route(
+                "/fail",
+                get(|| async { (StatusCode::SERVICE_UNAVAILABLE, "unavailable") }),
+            );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, router).await.unwrap();
+        });
+        format!("http://{}", addr)
+    }
+
+    fn cfg() -> HttpNodeCfg {
+        HttpNodeCfg {
+            allowed_hosts: vec!["127.0.0.1".to_string()],
+            timeout_secs: 1,
+            max_response_bytes: 1024,
+        }
+    }
+
+    fn get_payload(url: String) -> HttpPayload {
+        HttpPayload {
+            method: HttpMethod::Get,
+            url,
+            headers: HashMap::new(),
+            body: None,
+            body_type: HttpBodyType::Text,
+            extract: None,
+            timeout_secs: None,
+        }
+    }
+
+    #[test]
+    fn allow_list_matches_exact_and_wildcard_hosts() {
+        let allowed = vec!["api.example.com".to_string(), "*.internal.io".to_string()];
+        assert!(host_allowed("api.example.com", &allowed));
+        assert!(host_allowed("svc.internal.io", &allowed));
+        assert!(host_allowed("a.b.internal.io", &allowed));
+        assert!(!host_allowed("internal.io", &allowed));
+        assert!(!host_allowed("evilinternal.io", &allowed));
+        assert!(!host_allowed("example.com", &allowed));
+        assert!(!host_allowed("api.example.com.evil.net", &allowed));
+    }
+
+    #[tokio::test]
+    async fn allowed_host_is_requested() {
+        let base = mock_server().await;
+        let payload = get_payload(format!("{}/text", base));
+        let value = send_request(&cfg(), "n1", &payload, "").await.unwrap();
+        assert_eq!(value, json!("plain text"));
+    }
+
+    #[tokio::test]
+    async fn rejected_host_is_not_requested() {
+        let base = mock_server().await;
+        let payload = get_payload(base.replace("127.0.0.1", "localhost") + "/text");
+        let err = send_request(&cfg(), "n1", &payload, "").await.unwrap_err();
+        assert!(matches!(err, HttpNodeError::HostNotAllowed { host, .. } if host == "localhost"));
+    }
+
+    #[tokio::test]
+    async fn node_timeout_is_capped_by_config() {
+        let base = mock_server().await;
+        let mut payload = get_payload(format!("{}/slow", base));
+        payload.timeout_secs = Some(30);
+
+        let started = Instant::now();
+        let err = send_request(&cfg(), "n1", &payload, "").await.unwrap_err();
+        assert!(matches!(err, HttpNodeError::Timeout { .. }));
+        assert!(started.elapsed() < Duration::from_secs(4));
+    }
+
+    #[tokio::test]
+    async fn response_is_cut_off_at_max_bytes() {
+        let base = mock_server().await;
+        let payload = get_payload(format!("{}/large", base));
+        let err = send_request(&cfg(), "n1", &payload, "").await.unwrap_err();
+        assert!(matches!(
+            err,
+            HttpNodeError::ResponseTooLarge { limit: 1024, .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn extract_returns_pointed_value() {
+        let base = mock_server().await;
+        let mut payload = get_payload(format!("{}/json", base));
+        payload.extract = Some("/data/answer".to_string());
+        let value = send_request(&cfg(), "n1", &payload, "").await.unwrap();
+        assert_eq!(value, json!("42"));
+    }
+
+    #[tokio::test]
+    async fn extract_miss_is_an_error() {
+        let base = mock_server().await;
+        let mut payload = get_payload(format!("{}/json", base));
+        payload.extract = Some("/data/missing".to_string());
+        let err = send_request(&cfg(), "n1", &payload, "").await.unwrap_err();
+        assert!(
+            matches!(err, HttpNodeError::ExtractFailed { path, .. } if path == "/data/missing")
+        );
+    }
+
+    #[tokio::test]
+    async fn non_success_status_is_an_error() {
+        let base = mock_server().await;
+        let payload = get_payload(format!("{}/fail", base));
+        let err = send_request(&cfg(), "n1", &payload, "").await.unwrap_err();
+        assert!(matches!(err, HttpNodeError::Status { status: 503, .. }));
+    }
+
+    #[tokio::test]
+    async fn json_body_escapes_input() {
+        let base = mock_server().await;
+        let mut payload = get_payload(format!("{}/echo", base));
+        payload.method = HttpMethod::Post;
+        payload.body = Some(r#"{"q": "{{input}}"}"#.to_string());
+        payload.body_type = HttpBodyType::Json;
+        let input = "say \"hi\"\nthen \\ leave";
+
+        let value = send_request(&cfg(), "n1", &payload, input).await.unwrap();
+        assert_eq!(value["content_type"], "application/json");
+        let body: Value = serde_json::from_str(value["body"].as_str().unwrap()).unwrap();
+        assert_eq!(body, json!({ "q": input }));
+    }
+
+    #[tokio::test]
+    async fn explicit_content_type_is_kept() {
+        let base = mock_server().await;
+        let mut payload = get_payload(format!("{}/echo", base));
+        payload.method = HttpMethod::Post;
+        payload.body = Some("{{input}}".to_string());
+        payload
+            .headers
+            .insert("content-type".to_string(), "text/csv".to_string());
+
+        let value = send_request(&cfg(), "n1", &payload, "a,b").await.unwrap();
+        assert_eq!(value, json!({ "content_type": "text/csv", "body": "a,b" }));
+    }
+
+    #[test]
+    fn invalid_headers_are_reported() {
+        let mut payload = get_payload("https://example.com".to_string());
+        payload
+            .headers
+            .insert("X-Token".to_string(), "abc".to_string());
+        assert!(check_headers(&payload).is_ok());
+
+        payload
+            .headers
+            .insert("bad header".to_string(), "abc".to_string());
+        assert_eq!(
+            check_headers(&payload).unwrap_err(),
+            "invalid header name 'bad header'"
+        );
+
+        payload.headers.remove("bad header");
+        payload
+            .headers
+            .insert("X-Multi".to_string(), "a\nb".to_string());
+        assert_eq!(
+            check_headers(&payload).unwrap_err(),
+            "invalid value for header 'X-Multi'"
+        );
+    }
+}
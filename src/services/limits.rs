@@ -0,0 +1,75 @@
+use std::sync::OnceLock;
+
+use crate::{models::workflow::RunLimits, settings::RunLimitsCfg};
+
+static DEFAULT_LIMITS: OnceLock<RunLimitsCfg> = OnceLock::new();
+
+/// 启动时写入配置文件中的默认运行限制
+pub fn init_run_limits(cfg: RunLimitsCfg) {
+    let _ = DEFAULT_LIMITS.set(cfg);
+}
+
+/// 按 请求 > 图表 > 配置 的优先级合并出本次运行的限制；
+/// 请求和图表只能收紧限制，超过配置值时按配置值截断
+pub fn resolve_limits(request: Option<&RunLimits>, graph: Option<&RunLimits>) -> RunLimitsCfg {
+    let configured = DEFAULT_LIMITS.get().copied().unwrap_or_default();
+    resolve_with(configured, request, graph)
+}
+
+fn resolve_with(
+    configured: RunLimitsCfg,
+    request: Option<&RunLimits>,
+    graph: Option<&RunLimits>,
+) -> RunLimitsCfg {
+    let pick = |f: fn(&RunLimits) -> Option<u64>, configured: u64| {
+        request
+            .and_then(f)
+            .or_else(|| graph.and_then(f))
+            .map_or(configured, |value| value.min(configured))
+            .max(1)
+    };
+
+    RunLimitsCfg {
+        timeout_secs: pick(|l| l.timeout_secs, configured.timeout_secs),
+        max_node_executions: pick(|l| l.max_node_executions, configured.max_node_executions),
+        max_output_bytes: pick(|l| l.max_output_bytes, configured.max_output_bytes),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIGURED: RunLimitsCfg = RunLimitsCfg {
+        timeout_secs: 300,
+        max_node_executions: 200,
+        max_output_bytes: 1024,
+    };
+
+    #[test]
+    fn overrides_cannot_exceed_configured_limits() {
+        let request = RunLimits {
+            timeout_secs: Some(3600),
+            max_node_executions: Some(50),
+            max_output_bytes: None,
+        };
+        let graph = RunLimits {
+            timeout_secs: Some(10),
+            max_node_executions: None,
+            max_output_bytes: Some(1024 * 1024),
+        };
+
+        let limits = resolve_with(CONFIGURED, Some(&request), Some(&graph));
+        assert_eq!(limits.timeout_secs, 300);
+        assert_eq!(limits.max_node_executions, 50);
+        assert_eq!(limits.max_output_bytes, 1024);
+    }
+
+    #[test]
+    fn falls_back_to_configured_limits() {
+        let limits = resolve_with(CONFIGURED, None, None);
+        assert_eq!(limits.timeout_secs, 300);
+        assert_eq!(limits.max_node_executions, 200);
+        assert_eq!(limits.max_output_bytes, 1024);
+    }
+}
@@ -0,0 +1,6 @@
+pub mod executor;
+pub mod http_node;
+pub mod limits;
+pub mod runs;
+pub mod scheduler;
+pub mod trash;
@@ -0,0 +1,144 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{LazyLock, Mutex},
+    time::{Duration, Instant},
+};
+
+use tokio::task::AbortHandle;
+
+use crate::{
+    database::run::get_user_run_record,
+    error::Result,
+    models::workflow::{RunState, RunStatus, WorkflowOutput},
+    services::scheduler::{Ticket, queue_position},
+};
+
+/// 已结束的运行在内存中保留的时长
+const FINISHED_RUN_RETENTION: Duration = Duration::from_secs(3600);
+
+struct RunJob {
+    owner: String,
+    state: RunState,
+    ticket: u64,
+    abort: Option<AbortHandle>,
+    finished_at: Option<Instant>,
+}
+
+static RUNS: LazyLock<Mutex<HashMap<String, RunJob>>> = LazyLock::new(Default::default);
+
+pub enum CancelOutcome {
+    Cancelled(RunState),
+    AlreadyFinished,
+    NotFound,
+}
+
+/// 以给定的运行 ID 登记一次运行，在后台等待调度器分配执行槽后执行
+pub fn submit<F>(id: &str, owner: &str, graph_id: &str, ticket: Ticket, task: F)
+where
+    F: Future<Output = Result<WorkflowOutput, String>> + Send + 'static,
+{
+    let id = id.to_string();
+    let job = RunJob {
+        owner: owner.to_string(),
+        state: RunState {
+            id: id.clone(),
+            graph_id: graph_id.to_string(),
+            status: RunStatus::Queued,
+            output: None,
+            error: None,
+            queue_position: ticket.position(),
+        },
+        ticket: ticket.id(),
+        abort: None,
+        finished_at: None,
+    };
+
+    {
+        let mut runs = RUNS.lock().unwrap();
+        runs.retain(|_, job| {
+            job.finished_at
+                .is_none_or(|t| t.elapsed() < FINISHED_RUN_RETENTION)
+        });
+        runs.insert(id.clone(), job);
+    }
+
+    let run_id = id.clone();
+    let handle = tokio::spawn(async move {
+        let _permit = ticket.wait().await;
+        update(&run_id, |job| {
+            job.state.status = RunStatus::Running;
+            job.state.queue_position = None;
+        });
+        let result = task.await;
+        update(&run_id, |job| match result {
+            Ok(output) => {
+                job.state.status = RunStatus::Succeeded;
+                job.state.output = Some(output);
+            }
+            Err(error) => {
+                job.state.status = RunStatus::Failed;
+                job.state.error = Some(error);
+            }
+        });
+    });
+
+    // 任务可能在拿到句柄之前就已被取消，此时直接中止
+    match RUNS.lock().unwrap().get_mut(&id) {
+        Some(job) if !job.state.status.is_finished() => job.abort = Some(handle.abort_handle()),
+        Some(job) if job.state.status == RunStatus::Cancelled => handle.abort(),
+        _ => {}
+    }
+}
+
+/// 只更新尚未结束的运行，避免覆盖已取消的状态
+fn update(id: &str, f: impl FnOnce(&mut RunJob)) {
+    let mut runs = RUNS.lock().unwrap();
+    if let Some(job) = runs.get_mut(id) {
+        if job.state.status.is_finished() {
+            return;
+        }
+        f(job);
+        if job.state.status.is_finished() {
+            job.abort = None;
+            job.finished_at = Some(Instant::now());
+        }
+    }
+}
+
+/// 优先返回内存中的状态；已清理或重启后丢失的运行从运行历史中读取
+pub async fn get_run(id: &str, owner: &str) -> Result<Option<RunState>> {
+    if let Some(state) = get_live_run(id, owner) {
+        return Ok(Some(state));
+    }
+    let record = get_user_run_record(id, owner).await?;
+    Ok(record.map(RunState::from))
+}
+
+fn get_live_run(id: &str, owner: &str) -> Option<RunState> {
+    let runs = RUNS.lock().unwrap();
+    let job = runs.get(id).filter(|job| job.owner == owner)?;
+    let mut state = job.state.clone();
+    if state.status == RunStatus::Queued {
+        state.queue_position = queue_position(job.ticket);
+    }
+    Some(state)
+}
+
+pub fn cancel_run(id: &str, owner: &str) -> CancelOutcome {
+    let mut runs = RUNS.lock().unwrap();
+    let Some(job) = runs.get_mut(id).filter(|job| job.owner == owner) else {
+        return CancelOutcome::NotFound;
+    };
+    if job.state.status.is_finished() {
+        return CancelOutcome::AlreadyFinished;
+    }
+
+    if let Some(abort) = job.abort.take() {
+        abort.abort();
+    }
+    job.state.status = RunStatus::Cancelled;
+    job.state.queue_position = None;
+    job.finished_at = Some(Instant::now());
+    CancelOutcome::Cancelled(job.state.clone())
+}
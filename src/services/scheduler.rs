@@ -0,0 +1,414 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        Mutex, OnceLock,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use tokio::sync::oneshot;
+
+use crate::{
+    error::{Error, Result},
+    settings::SchedulerCfg,
+};
+
+static SCHEDULER: OnceLock<Scheduler> = OnceLock::new();
+
+/// 启动时写入调度配置；执行槽数或单用户并发为 0 时所有运行都会永远排队，直接拒绝
+pub fn init_scheduler(cfg: SchedulerCfg) -> Result<()> {
+    if cfg.workers == 0 {
+        return Err(Error::InvalidConfig(
+            "scheduler.workers must be greater than 0",
+        ));
+    }
+    if cfg.per_user_concurrency == 0 {
+        return Err(Error::InvalidConfig(
+            "scheduler.per_user_concurrency must be greater than 0",
+        ));
+    }
+    let _ = SCHEDULER.set(Scheduler::new(cfg));
+    Ok(())
+}
+
+fn scheduler() -> &'static Scheduler {
+    SCHEDULER.get_or_init(|| Scheduler::new(SchedulerCfg::default()))
+}
+
+struct Scheduler {
+    cfg: SchedulerCfg,
+    state: Mutex<SchedulerState>,
+    next_ticket: AtomicU64,
+}
+
+struct Waiter {
+    ticket: u64,
+    tx: oneshot::Sender<()>,
+}
+
+/// 排队中的运行按用户分队列，`rotation` 中只包含队列非空的用户，轮流出队保证公平
+#[derive(Default)]
+struct SchedulerState {
+    running: usize,
+    running_per_user: HashMap<String, usize>,
+    queues: HashMap<String, VecDeque<Waiter>>,
+    rotation: VecDeque<String>,
+    queued: usize,
+}
+
+impl SchedulerState {
+    fn user_running(&self, user: &str) -> usize {
+        self.running_per_user.get(user).copied().unwrap_or(0)
+    }
+
+    fn can_start(&self, user: &str, cfg: &SchedulerCfg) -> bool {
+        self.running < cfg.workers && self.user_running(user) < cfg.per_user_concurrency
+    }
+
+    fn start(&mut self, user: &str) {
+        self.running += 1;
+        *self.running_per_user.entry(user.to_string()).or_default() += 1;
+    }
+
+    fn release(&mut self, user: &str, cfg: &SchedulerCfg) {
+        self.running = self.running.saturating_sub(1);
+        if let Some(count) = self.running_per_user.get_mut(user) {
+            *count -= 1;
+            if *count == 0 {
+                self.running_per_user.remove(user);
+            }
+        }
+        self.dispatch(cfg);
+    }
+
+    /// 按用户轮转把空闲的执行槽分配给排队中的运行，已达上限的用户本轮跳过
+    fn dispatch(&mut self, cfg: &SchedulerCfg) {
+        let mut skipped = 0;
+        while self.running < cfg.workers && skipped < self.rotation.len() {
+            let Some(user) = self.rotation.pop_front() else {
+                break;
+            };
+            if self.user_running(&user) >= cfg.per_user_concurrency {
+                self.rotation.push_back(user);
+                skipped += 1;
+                continue;
+            }
+
+            let Some(queue) = self.queues.get_mut(&user) else {
+                continue;
+            };
+            let Some(waiter) = queue.pop_front() else {
+                self.queues.remove(&user);
+                continue;
+            };
+            self.queued -= 1;
+            if queue.is_empty() {
+                self.queues.remove(&user);
+            } else {
+                self.rotation.push_back(user.clone());
+            }
+
+            self.start(&user);
+            let _ = waiter.tx.send(());
+            skipped = 0;
+        }
+    }
+
+    /// 估算排队位置（从 1 开始）：假设各用户轮流出队，不考虑并发上限造成的跳过
+    fn position(&self, ticket: u64) -> Option<usize> {
+        let (user, index) = self.queues.iter().find_map(|(user, queue)| {
+            queue
+                .iter()
+                .position(|w| w.ticket == ticket)
+                .map(|index| (user, index))
+        })?;
+
+        let ahead_in_rounds: usize = self.queues.values().map(|q| q.len().min(index)).sum();
+        let ahead_in_round = self
+            .rotation
+            .iter()
+            .take_while(|u| *u != user)
+            .filter(|u| self.queues.get(*u).is_some_and(|q| q.len() > index))
+            .count();
+        Some(ahead_in_rounds + ahead_in_round + 1)
+    }
+
+    fn remove_waiter(&mut self, user: &str, ticket: u64) -> bool {
+        let Some(queue) = self.queues.get_mut(user) else {
+            return false;
+        };
+        let Some(index) = queue.iter().position(|w| w.ticket == ticket) else {
+            return false;
+        };
+        queue.remove(index);
+        self.queued -= 1;
+        if queue.is_empty() {
+            self.queues.remove(user);
+            self.rotation.retain(|u| u != user);
+        }
+        true
+    }
+}
+
+#[derive(Debug)]
+pub struct QueueFull;
+
+/// 一次运行的调度凭证，`wait` 完成后得到执行许可；未获得许可前丢弃会自动离开队列
+pub struct Ticket {
+    scheduler: &'static Scheduler,
+    id: u64,
+    user: String,
+    rx: Option<oneshot::Receiver<()>>,
+    granted: bool,
+}
+
+/// 执行许可，释放时把执行槽交给下一个排队的运行
+pub struct RunPermit {
+    scheduler: &'static Scheduler,
+    user: String,
+}
+
+/// 申请执行槽：有空闲时立即获得，否则进入该用户的队列；队列已满时返回 `QueueFull`
+pub fn enqueue(user: &str) -> Result<Ticket, QueueFull> {
+    scheduler().enqueue(user)
+}
+
+/// 查询排队位置，已开始执行或不存在时返回 `None`
+pub fn queue_position(ticket: u64) -> Option<usize> {
+    scheduler().position(ticket)
+}
+
+impl Scheduler {
+    fn new(cfg: SchedulerCfg) -> Self {
+        Scheduler {
+            cfg,
+            state: Mutex::new(SchedulerState::default()),
+            next_ticket: AtomicU64::new(1),
+        }
+    }
+
+    fn enqueue(&'static self, user: &str) -> Result<Ticket, QueueFull> {
+        let cfg = self.cfg;
+        let id = self.next_ticket.fetch_add(1, Ordering::Relaxed);
+        let mut state = self.state.lock().unwrap();
+
+        if state.can_start(user, &cfg) {
+            state.start(user);
+            return Ok(Ticket {
+                scheduler: self,
+                id,
+                user: user.to_string(),
+                rx: None,
+                granted: false,
+            });
+        }
+
+        if state.queued >= cfg.queue_capacity {
+            return Err(QueueFull);
+        }
+
+        let (tx, rx) = oneshot::channel();
+        state
+            .queues
+            .entry(user.to_string())
+            .or_default()
+            .push_back(Waiter { ticket: id, tx });
+        if !state.rotation.iter().any(|u| u == user) {
+            state.rotation.push_back(user.to_string());
+        }
+        state.queued += 1;
+
+        Ok(Ticket {
+            scheduler: self,
+            id,
+            user: user.to_string(),
+            rx: Some(rx),
+            granted: false,
+        })
+    }
+
+    fn position(&self, ticket: u64) -> Option<usize> {
+        self.state.lock().unwrap().position(ticket)
+    }
+
+    fn release(&self, user: &str) {
+        self.state.lock().unwrap().release(user, &self.cfg);
+    }
+}
+
+impl Ticket {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn position(&self) -> Option<usize> {
+        self.rx.as_ref().and(self.scheduler.position(self.id))
+    }
+
+    pub async fn wait(mut self) -> RunPermit {
+        if let Some(rx) = self.rx.take() {
+            // 发送端只会在分配执行槽后使用，不会被提前丢弃
+            let _ = rx.await;
+        }
+        self.granted = true;
+        RunPermit {
+            scheduler: self.scheduler,
+            user: self.user.clone(),
+        }
+    }
+}
+
+impl Drop for Ticket {
+    fn drop(&mut self) {
+        if self.granted {
+            return;
+        }
+        let mut state = self.scheduler.state.lock().unwrap();
+        // 仍在队列中则直接移除；否则执行槽已分配给它，需要归还
+        if !state.remove_waiter(&self.user, self.id) {
+            state.release(&self.user, &self.scheduler.cfg);
+        }
+    }
+}
+
+impl Drop for RunPermit {
+    fn drop(&mut self) {
+        self.scheduler.release(&self.user);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scheduler_with(
+        workers: usize,
+        per_user_concurrency: usize,
+        queue_capacity: usize,
+    ) -> &'static Scheduler {
+        Box::leak(Box::new(Scheduler::new(SchedulerCfg {
+            workers,
+            per_user_concurrency,
+            queue_capacity,
+        })))
+    }
+
+    /// 不等待地检查凭证是否已获得执行槽
+    fn granted(ticket: &mut Ticket) -> bool {
+        let Some(rx) = ticket.rx.as_mut() else {
+            return true;
+        };
+        if rx.try_recv().is_err() {
+            return false;
+        }
+        ticket.rx = None;
+        true
+    }
+
+    fn running(scheduler: &Scheduler) -> usize {
+        scheduler.state.lock().unwrap().running
+    }
+
+    #[test]
+    fn rejects_zero_workers_and_concurrency() {
+        let cfg = SchedulerCfg {
+            workers: 0,
+            ..SchedulerCfg::default()
+        };
+        assert!(init_scheduler(cfg).is_err());
+
+        let cfg = SchedulerCfg {
+            per_user_concurrency: 0,
+            ..SchedulerCfg::default()
+        };
+        assert!(init_scheduler(cfg).is_err());
+    }
+
+    #[test]
+    fn queue_full() {
+        let scheduler = scheduler_with(1, 1, 1);
+        let mut first = scheduler.enqueue("a").unwrap();
+        let mut second = scheduler.enqueue("b").unwrap();
+
+        assert!(granted(&mut first));
+        assert!(!granted(&mut second));
+        assert_eq!(second.position(), Some(1));
+        assert!(scheduler.enqueue("c").is_err());
+    }
+
+    #[tokio::test]
+    async fn drop_before_grant_leaves_queue() {
+        let scheduler = scheduler_with(1, 1, 1);
+        let first = scheduler.enqueue("a").unwrap();
+        let second = scheduler.enqueue("b").unwrap();
+        let second_id = second.id();
+
+        drop(second);
+        assert_eq!(scheduler.position(second_id), None);
+        assert_eq!(scheduler.state.lock().unwrap().queued, 0);
+
+        // 队列已空出，释放后不会把执行槽分给已离开的凭证
+        drop(first.wait().await);
+        assert_eq!(running(scheduler), 0);
+        let mut third = scheduler.enqueue("c").unwrap();
+        assert!(granted(&mut third));
+    }
+
+    #[tokio::test]
+    async fn drop_after_dispatch_returns_slot() {
+        let scheduler = scheduler_with(1, 1, 4);
+        let first = scheduler.enqueue("a").unwrap();
+        let mut second = scheduler.enqueue("b").unwrap();
+        let mut third = scheduler.enqueue("c").unwrap();
+
+        drop(first.wait().await);
+        assert!(granted(&mut second));
+        assert_eq!(running(scheduler), 1);
+
+        // 已分配执行槽但还没开始执行就被丢弃，执行槽转给下一个排队的运行
+        drop(second);
+        assert!(granted(&mut third));
+        assert_eq!(running(scheduler), 1);
+
+        drop(third.wait().await);
+        assert_eq!(running(scheduler), 0);
+    }
+
+    #[tokio::test]
+    async fn users_take_turns() {
+        let scheduler = scheduler_with(1, 1, 10);
+        let a1 = scheduler.enqueue("a").unwrap();
+        let mut a2 = scheduler.enqueue("a").unwrap();
+        let mut a3 = scheduler.enqueue("a").unwrap();
+        let mut b1 = scheduler.enqueue("b").unwrap();
+
+        assert_eq!(a2.position(), Some(1));
+        assert_eq!(b1.position(), Some(2));
+        assert_eq!(a3.position(), Some(3));
+
+        drop(a1.wait().await);
+        assert!(granted(&mut a2));
+        assert!(!granted(&mut b1));
+
+        drop(a2.wait().await);
+        assert!(granted(&mut b1));
+        assert!(!granted(&mut a3));
+
+        drop(b1.wait().await);
+        assert!(granted(&mut a3));
+    }
+
+    #[tokio::test]
+    async fn per_user_limit_lets_other_users_run() {
+        let scheduler = scheduler_with(2, 1, 10);
+        let a1 = scheduler.enqueue("a").unwrap();
+        let mut a2 = scheduler.enqueue("a").unwrap();
+        let mut b1 = scheduler.enqueue("b").unwrap();
+
+        assert!(!granted(&mut a2));
+        assert!(granted(&mut b1));
+
+        drop(a1.wait().await);
+        assert!(granted(&mut a2));
+    }
+}
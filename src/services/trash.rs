@@ -0,0 +1,18 @@
+use std::time::Duration;
+
+use tracing::{error, info};
+
+use crate::{database::graph::purge_expired_graphs, settings::TrashCfg};
+
+/// 定期清理回收站中超过保留期限的图表
+pub async fn run_trash_purger(cfg: TrashCfg) {
+    let mut interval = tokio::time::interval(Duration::from_secs(cfg.purge_interval_secs.max(1)));
+    loop {
+        interval.tick().await;
+        match purge_expired_graphs(cfg.retention_days).await {
+            Ok(0) => {}
+            Ok(count) => info!("已从回收站永久删除 {} 个图表", count),
+            Err(e) => error!("清理回收站失败: {}", e),
+        }
+    }
+}
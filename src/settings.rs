@@ -11,6 +11,14 @@ pub struct Settings {
     pub http: HttpCfg,
     pub surrealdb: SurrealdbCfg,
     pub jwt: JwtCfg,
+    #[serde(default)]
+    pub trash: TrashCfg,
+    #[serde(default)]
+    pub run_limits: RunLimitsCfg,
+    #[serde(default)]
+    pub scheduler: SchedulerCfg,
+    #[serde(default)]
+    pub http_node: HttpNodeCfg,
 }
 
 #[derive(Debug, Deserialize)]
@@ -18,6 +26,84 @@ pub struct HttpCfg {
     pub port: u16,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct TrashCfg {
+    /// 回收站中图表的保留天数，超过后自动永久删除
+    pub retention_days: u64,
+    /// 自动清理的执行间隔（秒）
+    pub purge_interval_secs: u64,
+}
+
+impl Default for TrashCfg {
+    fn default() -> Self {
+        TrashCfg {
+            retention_days: 30,
+            purge_interval_secs: 3600,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct RunLimitsCfg {
+    /// 单次运行的超时时间（秒）
+    pub timeout_secs: u64,
+    /// 单次运行最多执行的节点数
+    pub max_node_executions: u64,
+    /// 输出序列化后的最大字节数
+    pub max_output_bytes: u64,
+}
+
+impl Default for RunLimitsCfg {
+    fn default() -> Self {
+        RunLimitsCfg {
+            timeout_secs: 300,
+            max_node_executions: 200,
+            max_output_bytes: 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct SchedulerCfg {
+    /// 全局同时执行的运行数
+    pub workers: usize,
+    /// 单个用户同时执行的运行数
+    pub per_user_concurrency: usize,
+    /// 全局排队上限，超过后拒绝新的运行
+    pub queue_capacity: usize,
+}
+
+impl Default for SchedulerCfg {
+    fn default() -> Self {
+        SchedulerCfg {
+            workers: 8,
+            per_user_concurrency: 2,
+            queue_capacity: 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct HttpNodeCfg {
+    /// http 节点允许访问的主机，支持 `*.example.com`；为空时禁止所有请求
+    #[serde(default)]
+    pub allowed_hosts: Vec<String>,
+    /// 请求超时上限（秒）
+    pub timeout_secs: u64,
+    /// 响应体的最大字节数
+    pub max_response_bytes: u64,
+}
+
+impl Default for HttpNodeCfg {
+    fn default() -> Self {
+        HttpNodeCfg {
+            allowed_hosts: Vec::new(),
+            timeout_secs: 10,
+            max_response_bytes: 1024 * 1024,
+        }
+    }
+}
+
 impl Settings {
     pub fn load(config_path: &str) -> Result<Self> {
         let r = load_settings(config_path)?;
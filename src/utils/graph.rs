@@ -1,12 +1,123 @@
-use workflow_rs::model::node::{ControlNode, DataNode, NodeType};
+use std::collections::HashMap;
 
+use serde_json::{Map, Value};
+use surrealdb::Uuid;
+use workflow_rs::model::node::{DataNode, NodeType};
+
+use crate::{
+    models::{Edge, Node},
+    utils::node_type::{NodeTypeDef, find_node_type},
+};
+
+/// 未注册的类型在保存和运行前已被拒绝，这里的 identity 只是兜底
 pub(crate) fn get_workflow_node_type(t: &str) -> workflow_rs::model::node::NodeType {
-    match t {
-        "input" => NodeType::Data(DataNode::Input),
-        "prompt" => NodeType::Data(DataNode::Prompt),
-        "identity" => NodeType::Data(DataNode::Identity),
-        "branch" => NodeType::Control(ControlNode::Branch),
-        "llm" => NodeType::Data(DataNode::LLM),
-        _ => NodeType::Data(DataNode::Identity),
+    find_node_type(t)
+        .map(NodeTypeDef::workflow_type)
+        .unwrap_or(NodeType::Data(DataNode::Identity))
+}
+
+/// 为所有节点生成新的 ID，并同步改写边的端点以及起止节点
+pub(crate) fn regenerate_node_ids(
+    nodes: &mut [Node],
+    edges: &mut [Edge],
+    start_node: &mut String,
+    end_node: &mut String,
+) {
+    let mut id_map = HashMap::new();
+    for node in nodes.iter_mut() {
+        let new_id = Uuid::new_v4().to_string();
+        id_map.insert(std::mem::replace(&mut node.id, new_id.clone()), new_id);
+    }
+
+    let remap = |id: &mut String| {
+        if let Some(new_id) = id_map.get(id) {
+            *id = new_id.clone();
+        }
+    };
+    for edge in edges.iter_mut() {
+        remap(&mut edge.source);
+        remap(&mut edge.target);
+    }
+    remap(start_node);
+    remap(end_node);
+}
+
+/// 能接收运行时输入的起始节点类型
+const INPUT_NODE_KINDS: &[&str] = &["input", "http"];
+
+/// 用运行时输入覆盖起始节点的 `payload.input`，只作用于内存中的节点。
+///
+/// 起始节点不是 input 或 http 节点时输入不会被读取，返回错误信息；
+/// 起始节点不存在时留给结构校验报告
+pub(crate) fn apply_start_input(
+    nodes: &mut [Node],
+    start_node: &str,
+    input: Value,
+) -> Result<(), String> {
+    let Some(node) = nodes.iter_mut().find(|n| n.id == start_node) else {
+        return Ok(());
+    };
+    if !INPUT_NODE_KINDS.contains(&node.kind.as_str()) {
+        return Err(format!(
+            "start node '{}' ({}) does not accept input",
+            node.id, node.kind
+        ));
+    }
+    set_input(node, input);
+    Ok(())
+}
+
+fn set_input(node: &mut Node, input: Value) {
+    if !node.data.payload.is_object() {
+        node.data.payload = Value::Object(Map::new());
+    }
+    node.data.payload["input"] = input;
+}
+
+/// 为单节点测试构造最小图：输入节点直接使用测试输入，其他节点前接一个携带输入的起始节点
+pub(crate) fn build_node_test_graph(
+    mut node: Node,
+    input: Option<Value>,
+) -> (Vec<Node>, Vec<Edge>, String, String) {
+    let node_id = node.id.clone();
+    if node.kind == "input" {
+        if let Some(input) = input {
+            set_input(&mut node, input);
+        }
+        return (vec![node], vec![], node_id.clone(), node_id);
+    }
+
+    let mut start = Node::new_start_node();
+    start.id = format!("{}-test-input", node_id);
+    if let Some(input) = input {
+        set_input(&mut start, input);
+    }
+    let start_id = start.id.clone();
+    let nodes = vec![start, node];
+    let edges = vec![Edge::new(&start_id, &node_id)];
+    (nodes, edges, start_id, node_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn start_input_requires_input_or_http_start_node() {
+        let input = json!({ "type": "Single", "value": { "type": "Text", "value": "hi" } });
+
+        let mut nodes = vec![Node::new_start_node(), Node::new_llm("llm")];
+        apply_start_input(&mut nodes, "start", input.clone()).unwrap();
+        assert_eq!(nodes[0].data.payload["input"], input);
+
+        let err = apply_start_input(&mut nodes, "llm", input.clone()).unwrap_err();
+        assert_eq!(err, "start node 'llm' (llm) does not accept input");
+        assert!(nodes[1].data.payload.get("input").is_none());
+
+        nodes[1].kind = "http".to_string();
+        apply_start_input(&mut nodes, "llm", input.clone()).unwrap();
+        assert_eq!(nodes[1].data.payload["input"], input);
     }
 }
@@ -0,0 +1,13 @@
+use axum::http::{HeaderMap, header::IF_MATCH};
+
+/// 解析 `If-Match` 头中的版本号，去掉弱校验前缀和引号；`*` 视为不校验
+pub(crate) fn if_match_version(headers: &HeaderMap) -> Option<String> {
+    let value = headers.get(IF_MATCH)?.to_str().ok()?.trim();
+    let value = value.strip_prefix("W/").unwrap_or(value);
+    let value = value.trim_matches('"');
+    if value.is_empty() || value == "*" {
+        None
+    } else {
+        Some(value.to_string())
+    }
+}
@@ -1 +1,7 @@
 pub mod graph;
+pub mod headers;
+pub mod node_type;
+pub mod plan;
+pub mod prompt;
+pub mod template;
+pub mod validation;
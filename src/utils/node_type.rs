@@ -0,0 +1,142 @@
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+use workflow_rs::model::node::{ControlNode, DataNode, NodeType};
+
+use crate::{
+    models::{
+        Node,
+        node_type::{NodeCategory, NodeTypeInfo},
+        payload::{
+            BranchPayload, HttpPayload, IdentityPayload, InputPayload, LlmPayload, PromptPayload,
+        },
+    },
+    services::http_node::check_headers,
+};
+
+pub(crate) struct NodeTypeDef {
+    pub kind: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub category: NodeCategory,
+    default_payload: fn() -> Value,
+    workflow_type: fn() -> NodeType,
+    check_payload: fn(Value) -> Result<(), String>,
+}
+
+impl NodeTypeDef {
+    pub fn default_payload(&self) -> Value {
+        (self.default_payload)()
+    }
+
+    pub fn workflow_type(&self) -> NodeType {
+        (self.workflow_type)()
+    }
+
+    pub fn info(&self) -> NodeTypeInfo {
+        NodeTypeInfo {
+            kind: self.kind.to_string(),
+            name: self.name.to_string(),
+            description: self.description.to_string(),
+            category: self.category,
+            default_payload: self.default_payload(),
+        }
+    }
+}
+
+/// 支持的节点类型，`Node.type` 必须是其中之一
+pub(crate) const NODE_TYPES: &[NodeTypeDef] = &[
+    NodeTypeDef {
+        kind: "input",
+        name: "Input",
+        description: "Provides the run input to downstream nodes",
+        category: NodeCategory::Data,
+        default_payload: || {
+            json!({
+                "input": {
+                    "type": "Single",
+                    "value": {
+                        "type": "Text",
+                        "value": ""
+                    }
+                }
+            })
+        },
+        workflow_type: || NodeType::Data(DataNode::Input),
+        check_payload: check::<InputPayload>,
+    },
+    NodeTypeDef {
+        kind: "prompt",
+        name: "Prompt",
+        description: "Fills a template with the upstream value",
+        category: NodeCategory::Data,
+        default_payload: || json!({ "template": "{{input}}" }),
+        workflow_type: || NodeType::Data(DataNode::Prompt),
+        check_payload: check::<PromptPayload>,
+    },
+    NodeTypeDef {
+        kind: "llm",
+        name: "LLM",
+        description: "Sends the upstream value to a language model",
+        category: NodeCategory::Data,
+        default_payload: || json!({}),
+        workflow_type: || NodeType::Data(DataNode::LLM),
+        check_payload: check::<LlmPayload>,
+    },
+    NodeTypeDef {
+        kind: "identity",
+        name: "Identity",
+        description: "Passes the upstream value through unchanged",
+        category: NodeCategory::Data,
+        default_payload: || Value::Null,
+        workflow_type: || NodeType::Data(DataNode::Identity),
+        check_payload: check::<IdentityPayload>,
+    },
+    NodeTypeDef {
+        kind: "branch",
+        name: "Branch",
+        description: "Chooses one of its outgoing edges based on the upstream value",
+        category: NodeCategory::Control,
+        default_payload: || json!({ "input": "What is your choice?" }),
+        workflow_type: || NodeType::Control(ControlNode::Branch),
+        check_payload: check::<BranchPayload>,
+    },
+    NodeTypeDef {
+        kind: "http",
+        name: "HTTP request",
+        description: "Calls an allow-listed HTTP service and passes the response downstream",
+        category: NodeCategory::Data,
+        default_payload: || json!({ "method": "GET", "url": "https://example.com/api?q={{input}}" }),
+        // 分段执行时由执行器发出请求，下一段以携带响应的 input 节点作为起点
+        workflow_type: || NodeType::Data(DataNode::Input),
+        check_payload: check_http,
+    },
+];
+
+/// 按类型检查节点的 `payload`，空的 payload 视为空对象；未注册的类型不在这里检查
+pub(crate) fn check_node_payload(node: &Node) -> Result<(), String> {
+    let Some(def) = find_node_type(&node.kind) else {
+        return Ok(());
+    };
+    let payload = match &node.data.payload {
+        Value::Null => json!({}),
+        payload => payload.clone(),
+    };
+    (def.check_payload)(payload)
+        .map_err(|e| format!("node '{}' ({}): data.payload: {}", node.id, node.kind, e))
+}
+
+fn check<P: DeserializeOwned>(payload: Value) -> Result<(), String> {
+    serde_json::from_value::<P>(payload)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// 请求头不合法时在保存和运行前报错，而不是等到发出请求时才失败
+fn check_http(payload: Value) -> Result<(), String> {
+    let payload: HttpPayload = serde_json::from_value(payload).map_err(|e| e.to_string())?;
+    check_headers(&payload)
+}
+
+pub(crate) fn find_node_type(kind: &str) -> Option<&'static NodeTypeDef> {
+    NODE_TYPES.iter().find(|t| t.kind == kind)
+}
@@ -0,0 +1,236 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::{
+    models::{
+        Edge, Node,
+        workflow::{BranchPath, BranchPoint, ExecutionPlan, IdentityFallback},
+    },
+    utils::node_type::find_node_type,
+};
+
+/// 只根据图结构推导执行计划，不会执行任何节点。
+///
+/// workflow-rs 不提供调度顺序的查询接口，这里的顺序是按连线拓扑排序得到的估计，
+/// 与引擎实际的执行顺序可能不同（如分支只会走其中一条）
+pub(crate) fn build_execution_plan(
+    nodes: &[Node],
+    edges: &[Edge],
+    start_node: &str,
+    end_node: &str,
+) -> ExecutionPlan {
+    let node_ids: HashSet<&str> = nodes.iter().map(|n| n.id.as_str()).collect();
+    let mut adjacency: HashMap<&str, Vec<&Edge>> = HashMap::new();
+    for edge in edges {
+        if node_ids.contains(edge.source.as_str()) && node_ids.contains(edge.target.as_str()) {
+            adjacency
+                .entry(edge.source.as_str())
+                .or_default()
+                .push(edge);
+        }
+    }
+
+    let reachable = if node_ids.contains(start_node) {
+        bfs_order(start_node, &adjacency)
+    } else {
+        Vec::new()
+    };
+    let reachable_set: HashSet<&str> = reachable.iter().copied().collect();
+
+    let branches = nodes
+        .iter()
+        .filter(|n| n.kind == "branch" && reachable_set.contains(n.id.as_str()))
+        .map(|n| BranchPoint {
+            node_id: n.id.clone(),
+            paths: adjacency
+                .get(n.id.as_str())
+                .into_iter()
+                .flatten()
+                .map(|edge| BranchPath {
+                    handle: edge.source_handle.clone(),
+                    target: edge.target.clone(),
+                    nodes: bfs_order(&edge.target, &adjacency)
+                        .into_iter()
+                        .map(str::to_owned)
+                        .collect(),
+                })
+                .collect(),
+        })
+        .collect();
+
+    ExecutionPlan {
+        start_node: start_node.to_string(),
+        end_node: end_node.to_string(),
+        order: execution_order(&reachable, &adjacency),
+        reaches_end: reachable_set.contains(end_node),
+        branches,
+        unreachable: nodes
+            .iter()
+            .filter(|n| !reachable_set.contains(n.id.as_str()))
+            .map(|n| n.id.clone())
+            .collect(),
+        identity_fallbacks: nodes
+            .iter()
+            .filter(|n| find_node_type(&n.kind).is_none())
+            .map(|n| IdentityFallback {
+                node_id: n.id.clone(),
+                kind: n.kind.clone(),
+            })
+            .collect(),
+    }
+}
+
+fn bfs_order<'a>(start: &'a str, adjacency: &HashMap<&'a str, Vec<&'a Edge>>) -> Vec<&'a str> {
+    let mut visited = HashSet::from([start]);
+    let mut order = vec![start];
+    let mut queue = VecDeque::from([start]);
+    while let Some(id) = queue.pop_front() {
+        for edge in adjacency.get(id).into_iter().flatten() {
+            let next = edge.target.as_str();
+            if visited.insert(next) {
+                order.push(next);
+                queue.push_back(next);
+            }
+        }
+    }
+    order
+}
+
+/// 对可达子图做拓扑排序；分支形成的环无法排序，其中的节点按广度优先的发现顺序追加
+fn execution_order(reachable: &[&str], adjacency: &HashMap<&str, Vec<&Edge>>) -> Vec<String> {
+    let reachable_set: HashSet<&str> = reachable.iter().copied().collect();
+    let mut in_degree: HashMap<&str, usize> = reachable.iter().map(|&id| (id, 0)).collect();
+    for &id in reachable {
+        for edge in adjacency.get(id).into_iter().flatten() {
+            if let Some(degree) = in_degree.get_mut(edge.target.as_str()) {
+                *degree += 1;
+            }
+        }
+    }
+
+    let mut queue: VecDeque<&str> = reachable
+        .iter()
+        .copied()
+        .filter(|id| in_degree[id] == 0)
+        .collect();
+    let mut placed = HashSet::new();
+    let mut order = Vec::new();
+    while let Some(id) = queue.pop_front() {
+        placed.insert(id);
+        order.push(id.to_string());
+        for edge in adjacency.get(id).into_iter().flatten() {
+            let next = edge.target.as_str();
+            if !reachable_set.contains(next) {
+                continue;
+            }
+            if let Some(degree) = in_degree.get_mut(next) {
+                *degree -= 1;
+                if *degree == 0 {
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+
+    order.extend(
+        reachable
+            .iter()
+            .filter(|id| !placed.contains(*id))
+            .map(|id| id.to_string()),
+    );
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edge(source: &str, target: &str, handle: Option<&str>) -> Edge {
+        let mut edge = Edge::new(source, target);
+        edge.source_handle = handle.map(str::to_string);
+        edge
+    }
+
+    #[test]
+    fn plans_branch_paths_and_unreachable_nodes() {
+        let nodes = vec![
+            Node::new_start_node(),
+            Node::new_prompt("prompt", "{{input}}"),
+            Node::new_branch("branch"),
+            Node::new_llm("llm_a"),
+            Node::new_llm("llm_b"),
+            Node::new_llm("end"),
+            Node::new_llm("lonely"),
+        ];
+        let edges = vec![
+            edge("start", "prompt", None),
+            edge("prompt", "branch", None),
+            edge("branch", "llm_a", Some("yes")),
+            edge("branch", "llm_b", Some("no")),
+            edge("llm_a", "end", None),
+            edge("llm_b", "end", None),
+        ];
+
+        let plan = build_execution_plan(&nodes, &edges, "start", "end");
+        assert_eq!(
+            plan.order,
+            ["start", "prompt", "branch", "llm_a", "llm_b", "end"]
+        );
+        assert!(plan.reaches_end);
+        assert_eq!(plan.unreachable, ["lonely"]);
+        assert!(plan.identity_fallbacks.is_empty());
+
+        assert_eq!(plan.branches.len(), 1);
+        let paths = &plan.branches[0].paths;
+        assert_eq!(plan.branches[0].node_id, "branch");
+        assert_eq!(paths.len(), 2);
+        assert_eq!(paths[0].handle.as_deref(), Some("yes"));
+        assert_eq!(paths[0].target, "llm_a");
+        assert_eq!(paths[0].nodes, ["llm_a", "end"]);
+        assert_eq!(paths[1].handle.as_deref(), Some("no"));
+        assert_eq!(paths[1].nodes, ["llm_b", "end"]);
+    }
+
+    #[test]
+    fn appends_cycle_nodes_in_discovery_order() {
+        let nodes = vec![
+            Node::new_start_node(),
+            Node::new_branch("branch"),
+            Node::new_llm("llm"),
+            Node::new_llm("end"),
+        ];
+        let edges = vec![
+            edge("start", "branch", None),
+            edge("branch", "llm", Some("retry")),
+            edge("llm", "branch", None),
+            edge("branch", "end", Some("done")),
+        ];
+
+        let plan = build_execution_plan(&nodes, &edges, "start", "end");
+        assert_eq!(plan.order, ["start", "branch", "llm", "end"]);
+        assert!(plan.reaches_end);
+        assert!(plan.unreachable.is_empty());
+
+        let paths = &plan.branches[0].paths;
+        assert_eq!(paths[0].nodes, ["llm", "branch", "end"]);
+        assert_eq!(paths[1].nodes, ["end"]);
+    }
+
+    #[test]
+    fn reports_unknown_kinds_as_identity_fallbacks() {
+        let mut legacy = Node::new_llm("legacy");
+        legacy.kind = "retired".to_string();
+        let nodes = vec![Node::new_start_node(), legacy, Node::new_llm("end")];
+        let edges = vec![edge("start", "legacy", None), edge("legacy", "end", None)];
+
+        let plan = build_execution_plan(&nodes, &edges, "start", "end");
+        assert_eq!(plan.order, ["start", "legacy", "end"]);
+        assert_eq!(plan.identity_fallbacks.len(), 1);
+        assert_eq!(plan.identity_fallbacks[0].node_id, "legacy");
+        assert_eq!(plan.identity_fallbacks[0].kind, "retired");
+
+        let plan = build_execution_plan(&nodes, &edges, "missing", "end");
+        assert!(plan.order.is_empty());
+        assert!(!plan.reaches_end);
+        assert_eq!(plan.unreachable, ["start", "legacy", "end"]);
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+
+use crate::models::{Edge, Node, prompt::PromptAnalysis};
+
+/// 没有 `targetHandle` 的连线以该名称提供变量
+pub(crate) const DEFAULT_TEMPLATE_VARIABLE: &str = "input";
+
+/// 解析模板中的 `{{name}}` 占位符，返回去重后的变量名；占位符未闭合或为空时返回错误
+pub(crate) fn parse_template_variables(template: &str) -> Result<Vec<String>, String> {
+    let mut variables: Vec<String> = Vec::new();
+    let mut rest = template;
+    let mut offset = 0;
+    while let Some(start) = rest.find("{{") {
+        let after_open = &rest[start + 2 ..];
+        let Some(end) = after_open.find("}}") else {
+            return Err(format!("unclosed '{{{{' at offset {}", offset + start));
+        };
+        let name = after_open[.. end].trim();
+        if name.is_empty() {
+            return Err(format!("empty placeholder at offset {}", offset + start));
+        }
+        if !variables.iter().any(|v| v == name) {
+            variables.push(name.to_string());
+        }
+        let consumed = start + 2 + end + 2;
+        offset += consumed;
+        rest = &rest[consumed ..];
+    }
+    Ok(variables)
+}
+
+/// 用给定的变量替换模板中的 `{{name}}`，没有对应变量的占位符原样保留
+pub(crate) fn render_template(template: &str, vars: &HashMap<&str, String>) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let after_open = &rest[start + 2 ..];
+        let Some(end) = after_open.find("}}") else {
+            break;
+        };
+        rendered.push_str(&rest[.. start]);
+        match vars.get(after_open[.. end].trim()) {
+            Some(value) => rendered.push_str(value),
+            None => rendered.push_str(&rest[start .. start + 2 + end + 2]),
+        }
+        rest = &after_open[end + 2 ..];
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+/// 分析所有 prompt 节点的模板变量与上游连线是否对应
+pub(crate) fn analyze_prompt_nodes(nodes: &[Node], edges: &[Edge]) -> Vec<PromptAnalysis> {
+    nodes
+        .iter()
+        .filter(|n| n.kind == "prompt")
+        .map(|node| {
+            let template = node.data.payload["template"].as_str().unwrap_or_default();
+
+            let mut provided: Vec<String> = Vec::new();
+            for edge in edges.iter().filter(|e| e.target == node.id) {
+                let name = edge
+                    .target_handle
+                    .as_deref()
+                    .unwrap_or(DEFAULT_TEMPLATE_VARIABLE);
+                if !provided.iter().any(|p| p == name) {
+                    provided.push(name.to_string());
+                }
+            }
+
+            let (variables, syntax_error) = match parse_template_variables(template) {
+                Ok(variables) => (variables, None),
+                Err(e) => (Vec::new(), Some(e)),
+            };
+            let undefined = variables
+                .iter()
+                .filter(|v| !provided.contains(v))
+                .cloned()
+                .collect();
+            // 模板有语法错误时无法判断哪些输入被使用
+            let unused = if syntax_error.is_some() {
+                Vec::new()
+            } else {
+                provided
+                    .iter()
+                    .filter(|p| !variables.contains(p))
+                    .cloned()
+                    .collect()
+            };
+
+            PromptAnalysis {
+                node_id: node.id.clone(),
+                variables,
+                provided,
+                undefined,
+                unused,
+                syntax_error,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edge_with_handle(source: &str, target: &str, handle: Option<&str>) -> Edge {
+        let mut edge = Edge::new(source, target);
+        edge.target_handle = handle.map(str::to_string);
+        edge
+    }
+
+    #[test]
+    fn parses_variables_in_order_without_duplicates() {
+        let variables = parse_template_variables("{{ a }} and {{b}}, again {{a}}").unwrap();
+        assert_eq!(variables, ["a", "b"]);
+        assert!(
+            parse_template_variables("no placeholders")
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn rejects_unclosed_and_empty_placeholders() {
+        assert_eq!(
+            parse_template_variables("hi {{name}} {{oops").unwrap_err(),
+            "unclosed '{{' at offset 12"
+        );
+        assert_eq!(
+            parse_template_variables("x{{  }}").unwrap_err(),
+            "empty placeholder at offset 1"
+        );
+    }
+
+    #[test]
+    fn render_keeps_unknown_placeholders() {
+        let vars = HashMap::from([("name", "Ada".to_string())]);
+        assert_eq!(
+            render_template("{{ name }} meets {{other}}", &vars),
+            "Ada meets {{other}}"
+        );
+        assert_eq!(render_template("open {{name", &vars), "open {{name");
+    }
+
+    #[test]
+    fn maps_target_handles_to_variables() {
+        let nodes = vec![
+            Node::new_start_node(),
+            Node::new_llm("llm"),
+            Node::new_prompt("prompt", "{{input}} / {{context}} / {{missing}}"),
+        ];
+        let edges = vec![
+            edge_with_handle("start", "prompt", None),
+            edge_with_handle("llm", "prompt", Some("context")),
+            edge_with_handle("llm", "prompt", Some("extra")),
+            edge_with_handle("start", "prompt", Some("input")),
+        ];
+
+        let analysis = analyze_prompt_nodes(&nodes, &edges);
+        assert_eq!(analysis.len(), 1);
+        let analysis = &analysis[0];
+        assert_eq!(analysis.node_id, "prompt");
+        assert_eq!(analysis.variables, ["input", "context", "missing"]);
+        assert_eq!(analysis.provided, ["input", "context", "extra"]);
+        assert_eq!(analysis.undefined, ["missing"]);
+        assert_eq!(analysis.unused, ["extra"]);
+        assert!(analysis.syntax_error.is_none());
+    }
+
+    #[test]
+    fn syntax_error_skips_unused_inputs() {
+        let nodes = vec![Node::new_prompt("prompt", "{{input")];
+        let edges = vec![edge_with_handle("start", "prompt", None)];
+
+        let analysis = &analyze_prompt_nodes(&nodes, &edges)[0];
+        assert!(analysis.variables.is_empty());
+        assert!(analysis.unused.is_empty());
+        assert!(analysis.syntax_error.is_some());
+    }
+}
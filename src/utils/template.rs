@@ -0,0 +1,104 @@
+use crate::models::{Edge, Node, template::GraphTemplateSummary};
+
+pub(crate) struct GraphTemplate {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    build: fn() -> (Vec<Node>, Vec<Edge>),
+}
+
+impl GraphTemplate {
+    /// 生成模板的节点和边，起止节点固定为 "start" 和 "end"
+    pub fn build(&self) -> (Vec<Node>, Vec<Edge>) {
+        (self.build)()
+    }
+
+    pub fn summary(&self) -> GraphTemplateSummary {
+        GraphTemplateSummary {
+            id: self.id.to_string(),
+            name: self.name.to_string(),
+            description: self.description.to_string(),
+        }
+    }
+}
+
+pub(crate) const BLANK_TEMPLATE_ID: &str = "blank";
+
+pub(crate) const GRAPH_TEMPLATES: &[GraphTemplate] = &[
+    GraphTemplate {
+        id: BLANK_TEMPLATE_ID,
+        name: "Blank",
+        description: "Empty graph with only a start and an end node",
+        build: blank,
+    },
+    GraphTemplate {
+        id: "single-prompt",
+        name: "Single prompt",
+        description: "Fill a prompt template with the input and return it",
+        build: single_prompt,
+    },
+    GraphTemplate {
+        id: "prompt-llm",
+        name: "Prompt to LLM",
+        description: "Fill a prompt template and send it to an LLM",
+        build: prompt_llm,
+    },
+    GraphTemplate {
+        id: "branching",
+        name: "Branching",
+        description: "Let a branch node choose between two prompts",
+        build: branching,
+    },
+];
+
+pub(crate) fn find_template(id: &str) -> Option<&'static GraphTemplate> {
+    GRAPH_TEMPLATES.iter().find(|t| t.id == id)
+}
+
+fn blank() -> (Vec<Node>, Vec<Edge>) {
+    let nodes = vec![Node::new_start_node(), Node::new_end_node()];
+    (nodes, Vec::new())
+}
+
+fn single_prompt() -> (Vec<Node>, Vec<Edge>) {
+    let nodes = vec![
+        Node::new_start_node(),
+        Node::new_prompt("prompt", "{{input}}").with_position(200.0, 0.0),
+        Node::new_end_node().with_position(400.0, 0.0),
+    ];
+    let edges = vec![Edge::new("start", "prompt"), Edge::new("prompt", "end")];
+    (nodes, edges)
+}
+
+fn prompt_llm() -> (Vec<Node>, Vec<Edge>) {
+    let nodes = vec![
+        Node::new_start_node(),
+        Node::new_prompt("prompt", "{{input}}").with_position(200.0, 0.0),
+        Node::new_llm("llm").with_position(400.0, 0.0),
+        Node::new_end_node().with_position(600.0, 0.0),
+    ];
+    let edges = vec![
+        Edge::new("start", "prompt"),
+        Edge::new("prompt", "llm"),
+        Edge::new("llm", "end"),
+    ];
+    (nodes, edges)
+}
+
+fn branching() -> (Vec<Node>, Vec<Edge>) {
+    let nodes = vec![
+        Node::new_start_node(),
+        Node::new_branch("branch").with_position(200.0, 0.0),
+        Node::new_prompt("prompt_a", "Option A: {{input}}").with_position(400.0, -100.0),
+        Node::new_prompt("prompt_b", "Option B: {{input}}").with_position(400.0, 100.0),
+        Node::new_end_node().with_position(600.0, 0.0),
+    ];
+    let edges = vec![
+        Edge::new("start", "branch"),
+        Edge::new("branch", "prompt_a"),
+        Edge::new("branch", "prompt_b"),
+        Edge::new("prompt_a", "end"),
+        Edge::new("prompt_b", "end"),
+    ];
+    (nodes, edges)
+}
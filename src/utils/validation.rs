@@ -0,0 +1,428 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::{
+    models::{
+        Edge, Node,
+        validation::{GraphIssue, IssueCode, IssueSeverity},
+    },
+    utils::{node_type::find_node_type, prompt::analyze_prompt_nodes},
+};
+
+/// 对图做结构校验：悬空的边、缺失的起止节点、不可达节点以及没有分支节点的环
+pub(crate) fn validate_graph_structure(
+    nodes: &[Node],
+    edges: &[Edge],
+    start_node: &str,
+    end_node: &str,
+) -> Vec<GraphIssue> {
+    let mut issues = Vec::new();
+
+    let mut node_map: HashMap<&str, &Node> = HashMap::new();
+    for node in nodes {
+        if node_map.insert(node.id.as_str(), node).is_some() {
+            issues.push(GraphIssue::node(
+                IssueSeverity::Error,
+                IssueCode::DuplicateNodeId,
+                &node.id,
+                format!("node id '{}' is used more than once", node.id),
+            ));
+        }
+        if find_node_type(&node.kind).is_none() {
+            issues.push(GraphIssue::node(
+                IssueSeverity::Error,
+                IssueCode::UnknownNodeKind,
+                &node.id,
+                unknown_kind_message(node),
+            ));
+        }
+    }
+
+    let mut edge_ids = HashSet::new();
+    let mut adjacency: HashMap<&str, Vec<&str>> = HashMap::new();
+    for edge in edges {
+        if !edge_ids.insert(edge.id.as_str()) {
+            issues.push(GraphIssue::edge(
+                IssueSeverity::Error,
+                IssueCode::DuplicateEdgeId,
+                &edge.id,
+                format!("edge id '{}' is used more than once", edge.id),
+            ));
+        }
+
+        let source_exists = node_map.contains_key(edge.source.as_str());
+        let target_exists = node_map.contains_key(edge.target.as_str());
+        if !source_exists {
+            issues.push(GraphIssue::edge(
+                IssueSeverity::Error,
+                IssueCode::EdgeSourceNotFound,
+                &edge.id,
+                format!("edge source '{}' does not exist", edge.source),
+            ));
+        }
+        if !target_exists {
+            issues.push(GraphIssue::edge(
+                IssueSeverity::Error,
+                IssueCode::EdgeTargetNotFound,
+                &edge.id,
+                format!("edge target '{}' does not exist", edge.target),
+            ));
+        }
+        if source_exists && target_exists {
+            adjacency
+                .entry(edge.source.as_str())
+                .or_default()
+                .push(edge.target.as_str());
+        }
+    }
+
+    let start_exists = node_map.contains_key(start_node);
+    let end_exists = node_map.contains_key(end_node);
+    if !start_exists {
+        issues.push(GraphIssue::node(
+            IssueSeverity::Error,
+            IssueCode::StartNodeNotFound,
+            start_node,
+            format!("start node '{}' does not exist", start_node),
+        ));
+    }
+    if !end_exists {
+        issues.push(GraphIssue::node(
+            IssueSeverity::Error,
+            IssueCode::EndNodeNotFound,
+            end_node,
+            format!("end node '{}' does not exist", end_node),
+        ));
+    }
+
+    if start_exists {
+        let reachable = reachable_from(start_node, &adjacency);
+        if end_exists && !reachable.contains(end_node) {
+            issues.push(GraphIssue::node(
+                IssueSeverity::Error,
+                IssueCode::EndNodeUnreachable,
+                end_node,
+                format!(
+                    "end node '{}' is not reachable from the start node",
+                    end_node
+                ),
+            ));
+        }
+        for node in nodes {
+            if node.id != end_node && !reachable.contains(node.id.as_str()) {
+                issues.push(GraphIssue::node(
+                    IssueSeverity::Warning,
+                    IssueCode::UnreachableNode,
+                    &node.id,
+                    format!("node '{}' is not reachable from the start node", node.id),
+                ));
+            }
+        }
+    }
+
+    // 模板检查只给出警告，不阻止保存或运行
+    for analysis in analyze_prompt_nodes(nodes, edges) {
+        if let Some(error) = &analysis.syntax_error {
+            issues.push(GraphIssue::node(
+                IssueSeverity::Warning,
+                IssueCode::TemplateSyntaxError,
+                &analysis.node_id,
+                format!("prompt template of node '{}': {}", analysis.node_id, error),
+            ));
+        }
+        for variable in &analysis.undefined {
+            issues.push(GraphIssue::node(
+                IssueSeverity::Warning,
+                IssueCode::UndefinedTemplateVariable,
+                &analysis.node_id,
+                format!(
+                    "prompt template of node '{}' uses '{}' but no incoming edge provides it",
+                    analysis.node_id, variable
+                ),
+            ));
+        }
+        for variable in &analysis.unused {
+            issues.push(GraphIssue::node(
+                IssueSeverity::Warning,
+                IssueCode::UnusedTemplateInput,
+                &analysis.node_id,
+                format!(
+                    "input '{}' of node '{}' is not used by its prompt template",
+                    variable, analysis.node_id
+                ),
+            ));
+        }
+    }
+
+    for component in cyclic_components(nodes, &adjacency) {
+        let has_branch = component
+            .iter()
+            .any(|id| node_map.get(id).is_some_and(|n| n.kind == "branch"));
+        if has_branch {
+            continue;
+        }
+        for id in component {
+            issues.push(GraphIssue::node(
+                IssueSeverity::Error,
+                IssueCode::CycleWithoutBranch,
+                id,
+                format!("node '{}' is part of a cycle without a branch node", id),
+            ));
+        }
+    }
+
+    issues
+}
+
+fn unknown_kind_message(node: &Node) -> String {
+    format!("node '{}' has unknown type '{}'", node.id, node.kind)
+}
+
+/// 找出第一个类型未注册的节点，保存图表时无论是否严格模式都会拒绝
+pub(crate) fn find_unknown_node_kind(nodes: &[Node]) -> Option<String> {
+    nodes
+        .iter()
+        .find(|n| find_node_type(&n.kind).is_none())
+        .map(unknown_kind_message)
+}
+
+fn reachable_from<'a>(
+    start: &'a str,
+    adjacency: &HashMap<&'a str, Vec<&'a str>>,
+) -> HashSet<&'a str> {
+    let mut visited = HashSet::from([start]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(id) = queue.pop_front() {
+        for &next in adjacency.get(id).into_iter().flatten() {
+            if visited.insert(next) {
+                queue.push_back(next);
+            }
+        }
+    }
+    visited
+}
+
+/// Tarjan 强连通分量，只返回真正成环的分量（多个节点或自环）
+///
+/// 用显式栈代替递归，长链图不会把线程栈压爆
+fn cyclic_components<'a>(
+    nodes: &'a [Node],
+    adjacency: &HashMap<&'a str, Vec<&'a str>>,
+) -> Vec<Vec<&'a str>> {
+    let mut index = 0;
+    let mut indices: HashMap<&'a str, usize> = HashMap::new();
+    let mut low_links: HashMap<&'a str, usize> = HashMap::new();
+    let mut stack: Vec<&'a str> = Vec::new();
+    let mut on_stack: HashSet<&'a str> = HashSet::new();
+    let mut components = Vec::new();
+
+    for node in nodes {
+        let root = node.id.as_str();
+        if indices.contains_key(root) {
+            continue;
+        }
+
+        // 调用栈：节点和下一个待访问的出边下标
+        let mut frames: Vec<(&'a str, usize)> = vec![(root, 0)];
+        indices.insert(root, index);
+        low_links.insert(root, index);
+        index += 1;
+        stack.push(root);
+        on_stack.insert(root);
+
+        while let Some(frame) = frames.last_mut() {
+            let (id, cursor) = *frame;
+            let targets = adjacency.get(id).map(Vec::as_slice).unwrap_or(&[]);
+
+            if let Some(&next) = targets.get(cursor) {
+                frame.1 += 1;
+                if !indices.contains_key(next) {
+                    indices.insert(next, index);
+                    low_links.insert(next, index);
+                    index += 1;
+                    stack.push(next);
+                    on_stack.insert(next);
+                    frames.push((next, 0));
+                } else if on_stack.contains(next) {
+                    let low = low_links[id].min(indices[next]);
+                    low_links.insert(id, low);
+                }
+                continue;
+            }
+
+            frames.pop();
+            if let Some(&(parent, _)) = frames.last() {
+                let low = low_links[parent].min(low_links[id]);
+                low_links.insert(parent, low);
+            }
+
+            if low_links[id] == indices[id] {
+                let mut component = Vec::new();
+                while let Some(member) = stack.pop() {
+                    on_stack.remove(member);
+                    component.push(member);
+                    if member == id {
+                        break;
+                    }
+                }
+                if component.len() > 1 || targets.contains(&id) {
+                    components.push(component);
+                }
+            }
+        }
+    }
+    components
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn components_of(nodes: &[Node], edges: &[(usize, usize)]) -> Vec<Vec<String>> {
+        let mut adjacency: HashMap<&str, Vec<&str>> = HashMap::new();
+        for &(from, to) in edges {
+            adjacency
+                .entry(nodes[from].id.as_str())
+                .or_default()
+                .push(nodes[to].id.as_str());
+        }
+        let mut components: Vec<Vec<String>> = cyclic_components(nodes, &adjacency)
+            .into_iter()
+            .map(|c| {
+                let mut c: Vec<String> = c.into_iter().map(str::to_string).collect();
+                c.sort();
+                c
+            })
+            .collect();
+        components.sort();
+        components
+    }
+
+    /// 校验结果中的问题代码和对应的节点或连线 ID
+    fn issues_of(
+        nodes: &[Node],
+        edges: &[(&str, &str)],
+        start_node: &str,
+        end_node: &str,
+    ) -> Vec<(IssueCode, String)> {
+        let edges: Vec<Edge> = edges.iter().map(|(s, t)| Edge::new(s, t)).collect();
+        validate_graph_structure(nodes, &edges, start_node, end_node)
+            .into_iter()
+            .map(|issue| {
+                (
+                    issue.code,
+                    issue.node_id.or(issue.edge_id).unwrap_or_default(),
+                )
+            })
+            .collect()
+    }
+
+    fn llm_nodes(count: usize) -> Vec<Node> {
+        (0 .. count)
+            .map(|i| Node::new_llm(&format!("n{i}")))
+            .collect()
+    }
+
+    #[test]
+    fn long_chain_does_not_overflow() {
+        let nodes = llm_nodes(200_000);
+        let edges: Vec<_> = (1 .. nodes.len()).map(|i| (i - 1, i)).collect();
+        assert!(components_of(&nodes, &edges).is_empty());
+    }
+
+    #[test]
+    fn finds_cycles_and_self_loops() {
+        let nodes = llm_nodes(5);
+        let edges = [(0, 1), (1, 2), (2, 0), (2, 3), (4, 4)];
+        assert_eq!(
+            components_of(&nodes, &edges),
+            vec![vec!["n0", "n1", "n2"], vec!["n4"]]
+        );
+    }
+
+    #[test]
+    fn valid_graph_has_no_issues() {
+        let nodes = llm_nodes(3);
+        assert!(issues_of(&nodes, &[("n0", "n1"), ("n1", "n2")], "n0", "n2").is_empty());
+    }
+
+    #[test]
+    fn reports_dangling_edges() {
+        let nodes = llm_nodes(2);
+        let issues = issues_of(
+            &nodes,
+            &[("n0", "n1"), ("n0", "ghost"), ("phantom", "n1")],
+            "n0",
+            "n1",
+        );
+        assert_eq!(
+            issues,
+            [
+                (IssueCode::EdgeTargetNotFound, "e-n0-ghost".to_string()),
+                (IssueCode::EdgeSourceNotFound, "e-phantom-n1".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_missing_start_and_end_nodes() {
+        let nodes = llm_nodes(2);
+        let issues = issues_of(&nodes, &[("n0", "n1")], "missing", "gone");
+        assert_eq!(
+            issues,
+            [
+                (IssueCode::StartNodeNotFound, "missing".to_string()),
+                (IssueCode::EndNodeNotFound, "gone".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_unreachable_end_and_nodes() {
+        let nodes = llm_nodes(4);
+        let issues = issues_of(&nodes, &[("n0", "n1")], "n0", "n3");
+        assert_eq!(
+            issues,
+            [
+                (IssueCode::EndNodeUnreachable, "n3".to_string()),
+                (IssueCode::UnreachableNode, "n2".to_string()),
+            ]
+        );
+        let report = validate_graph_structure(&nodes, &[Edge::new("n0", "n1")], "n0", "n3");
+        assert_eq!(report[0].severity, IssueSeverity::Error);
+        assert_eq!(report[1].severity, IssueSeverity::Warning);
+    }
+
+    #[test]
+    fn reports_duplicate_ids() {
+        let mut nodes = llm_nodes(2);
+        nodes.push(Node::new_llm("n1"));
+        let issues = issues_of(&nodes, &[("n0", "n1"), ("n0", "n1")], "n0", "n1");
+        assert_eq!(
+            issues,
+            [
+                (IssueCode::DuplicateNodeId, "n1".to_string()),
+                (IssueCode::DuplicateEdgeId, "e-n0-n1".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn cycle_needs_a_branch_node() {
+        let edges = [("n0", "n1"), ("n1", "n2"), ("n2", "n1"), ("n2", "n3")];
+
+        let nodes = llm_nodes(4);
+        let mut issues = issues_of(&nodes, &edges, "n0", "n3");
+        issues.sort_by(|a, b| a.1.cmp(&b.1));
+        assert_eq!(
+            issues,
+            [
+                (IssueCode::CycleWithoutBranch, "n1".to_string()),
+                (IssueCode::CycleWithoutBranch, "n2".to_string()),
+            ]
+        );
+
+        let mut nodes = llm_nodes(4);
+        nodes[2] = Node::new_branch("n2");
+        assert!(issues_of(&nodes, &edges, "n0", "n3").is_empty());
+    }
+}